@@ -16,7 +16,7 @@ use std::time::Duration;
 use std::{str, time::Instant};
 
 use anyhow::Context;
-use chrono::Utc;
+use chrono::{DateTime, TimeZone, Utc};
 use clap::builder::TypedValueParser;
 use clap::{Arg, Parser};
 use collector::compare::compare_artifacts;
@@ -38,10 +38,13 @@ use collector::compile::benchmark::codegen_backend::CodegenBackend;
 use collector::compile::benchmark::profile::Profile;
 use collector::compile::benchmark::scenario::Scenario;
 use collector::compile::benchmark::{
-    compile_benchmark_dir, get_compile_benchmarks, ArtifactType, Benchmark, BenchmarkName,
+    compile_benchmark_dir, get_compile_benchmarks, shuffle_benchmarks, ArtifactType, Benchmark,
+    BenchmarkError, BenchmarkName,
+};
+use collector::compile::execute::bencher::{BenchProcessor, StatsProcessor};
+use collector::compile::execute::profiler::{
+    validate_name_template, OutputLayout, ProfileProcessor, Profiler,
 };
-use collector::compile::execute::bencher::BenchProcessor;
-use collector::compile::execute::profiler::{ProfileProcessor, Profiler};
 use collector::runtime::{
     bench_runtime, get_runtime_benchmark_groups, prepare_runtime_benchmark_suite,
     runtime_benchmark_dir, BenchmarkFilter, BenchmarkSuite, BenchmarkSuiteCompilation,
@@ -62,6 +65,21 @@ fn n_normal_benchmarks_remaining(n: usize) -> String {
     format!("{} normal benchmark{} remaining", n, suffix)
 }
 
+/// When `--progress` is passed, one JSON object per benchmarking event is
+/// printed to stdout so a supervising process can track the run (and detect
+/// stalls) without scraping the human-readable stderr output.
+static PROGRESS_EVENTS: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+fn enable_progress_events() {
+    PROGRESS_EVENTS.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn emit_progress(event: serde_json::Value) {
+    if PROGRESS_EVENTS.load(std::sync::atomic::Ordering::SeqCst) {
+        println!("{event}");
+    }
+}
+
 struct BenchmarkErrors(usize);
 
 impl BenchmarkErrors {
@@ -95,12 +113,32 @@ struct CompileBenchmarkConfig {
     benchmarks: Vec<Benchmark>,
     profiles: Vec<Profile>,
     scenarios: Vec<Scenario>,
+    /// Restricts `IncrPatched` runs to patches with these indices; `None`
+    /// runs every patch.
+    patch_indices: Option<Vec<usize>>,
     backends: Vec<CodegenBackend>,
     iterations: Option<usize>,
+    /// How many timed iterations to execute and discard before the measured
+    /// ones.
+    warmup: usize,
+    /// Cap on the parallelism of the untimed preparation phase.
+    prep_parallelism: Option<usize>,
     is_self_profile: bool,
     bench_rustc: bool,
+    /// Re-attempt benchmarks whose previous run for this artifact errored,
+    /// instead of skipping everything that was already benchmarked.
+    rerun_errored: bool,
+    /// How many times a flaky measurement (missing or multiplexed perf
+    /// counters) is retried before the benchmark is recorded as errored.
+    max_retries: u8,
+    /// Stop at the first benchmark that errors, instead of recording the
+    /// error and moving on to the next benchmark (the default).
+    fail_fast: bool,
 }
 
+/// Default retry budget for flaky measurements.
+const DEFAULT_MAX_RETRIES: u8 = 5;
+
 struct RuntimeBenchmarkConfig {
     runtime_suite: BenchmarkSuite,
     filter: BenchmarkFilter,
@@ -123,8 +161,18 @@ struct SharedBenchmarkConfig {
 }
 
 fn check_measureme_installed() -> Result<(), String> {
-    let not_installed = IntoIterator::into_iter(["summarize", "crox", "flamegraph"])
+    check_measureme_tools_installed(&["summarize", "crox", "flamegraph"])
+}
+
+fn check_summarize_installed() -> Result<(), String> {
+    check_measureme_tools_installed(&["summarize"])
+}
+
+fn check_measureme_tools_installed(tools: &[&str]) -> Result<(), String> {
+    let not_installed = tools
+        .iter()
         .filter(|n| !is_installed(n))
+        .copied()
         .collect::<Vec<_>>();
     if not_installed.is_empty() {
         Ok(())
@@ -133,6 +181,293 @@ fn check_measureme_installed() -> Result<(), String> {
     }
 }
 
+/// Returns the available space in bytes on the filesystem containing `path`,
+/// or `None` on platforms where we cannot cheaply determine it.
+#[cfg(unix)]
+fn available_disk_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } == 0 {
+        Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn available_disk_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Fails fast when the temp filesystem has less than `min_free_gb` gigabytes
+/// available. A value of 0 disables the check.
+fn check_free_disk_space(min_free_gb: u64) -> anyhow::Result<()> {
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    if min_free_gb == 0 {
+        return Ok(());
+    }
+    let tmp = std::env::temp_dir();
+    if let Some(available) = available_disk_space(&tmp) {
+        if available < min_free_gb * GB {
+            anyhow::bail!(
+                "only {:.1} GB free on {}, but at least {} GB is required; \
+                 free up space or lower --min-free-disk-gb",
+                available as f64 / GB as f64,
+                tmp.display(),
+                min_free_gb
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Maps an artifact id to a stable synthetic commit date, so that repeated
+/// `bench_local --overwrite` runs with the same id resolve to the same
+/// `Commit` instead of accumulating timestamped entries.
+fn deterministic_commit_date(id: &str) -> DateTime<Utc> {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    id.hash(&mut hasher);
+    // Spread the synthetic dates over roughly thirty years after the Unix
+    // epoch, well before any real artifact, so they cannot collide with dates
+    // of benchmarked commits.
+    let seconds = hasher.finish() % (30 * 365 * 24 * 60 * 60);
+    Utc.timestamp_opt(seconds as i64, 0).unwrap()
+}
+
+/// Matches `name` against a shell-style pattern in which `*` matches any
+/// (possibly empty) substring; no other metacharacters are special.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    // The pattern contained no `*`, so it must match exactly.
+    rest.is_empty()
+}
+
+/// Picks a seed for `--shuffle` when none was given on the command line. The
+/// seed is printed, so the resulting order stays reproducible.
+fn generate_shuffle_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Best-effort description of the machine benchmarks are executed on, stored
+/// with each artifact so analysis tools can warn when results from different
+/// environments are being compared. Every field may be absent; gathering the
+/// fingerprint must never fail a run.
+#[derive(serde::Serialize)]
+struct EnvironmentFingerprint {
+    hostname: Option<String>,
+    cpu_model: Option<String>,
+    os_version: Option<String>,
+    perf_version: Option<String>,
+    perf_events: String,
+    /// The target triple the artifact was benchmarked for. Unlike the other
+    /// fields this one is load-bearing: the site refuses to compare artifacts
+    /// whose triples differ, since cross-architecture deltas are meaningless.
+    triple: String,
+}
+
+fn gather_environment_fingerprint(triple: &str) -> EnvironmentFingerprint {
+    let from_cmd = |cmd: &str, args: &[&str]| {
+        Command::new(cmd)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let cpu_model = fs::read_to_string("/proc/cpuinfo").ok().and_then(|info| {
+        info.lines()
+            .find(|line| line.starts_with("model name"))
+            .and_then(|line| line.split(':').nth(1))
+            .map(|name| name.trim().to_string())
+    });
+    EnvironmentFingerprint {
+        hostname: from_cmd("hostname", &[]),
+        cpu_model,
+        os_version: from_cmd("uname", &["-sr"]),
+        perf_version: from_cmd("perf", &["--version"]),
+        perf_events: PERF_STAT_EVENTS.to_string(),
+        triple: triple.to_string(),
+    }
+}
+
+/// Installs a SIGINT handler that requests a clean stop: whatever is
+/// currently being measured finishes and is recorded, but nothing new is
+/// started. A second Ctrl-C aborts immediately without waiting.
+#[cfg(unix)]
+fn install_stop_handler() {
+    extern "C" fn handle_sigint(_: libc::c_int) {
+        if collector::stop_requested() {
+            // The only async-signal-safe way out.
+            unsafe { libc::_exit(130) };
+        }
+        collector::request_stop();
+    }
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            handle_sigint as *const () as libc::sighandler_t,
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn install_stop_handler() {}
+
+/// The perf events recorded by `rustc-fake` when benchmarking.
+/// Keep this list in sync with the default `perf stat` event list there;
+/// `--stats` narrows it via the `RUSTC_PERF_STAT_EVENTS` environment variable.
+const PERF_STAT_EVENTS: &str = "instructions:u,cycles:u,task-clock,cpu-clock,faults,context-switches,branch-misses,cache-misses";
+
+/// Stats recorded by `rustc-fake` itself rather than by perf. They may be
+/// named in `--stats` for symmetry, but they are not perf events and are
+/// always recorded regardless of the selection.
+const NON_PERF_STATS: &[&str] = &["wall-time", "max-rss"];
+
+/// Validates a `--stats` selection against the known stats and returns the
+/// corresponding `perf stat` event list, so a typo errors out before any
+/// benchmarking starts.
+fn narrowed_perf_stat_events(stats: &str) -> anyhow::Result<String> {
+    let known: Vec<&str> = PERF_STAT_EVENTS.split(',').collect();
+    let mut events = Vec::new();
+    for stat in stats.split(',') {
+        let stat = stat.trim();
+        if known.contains(&stat) {
+            events.push(stat);
+        } else if !NON_PERF_STATS.contains(&stat) {
+            anyhow::bail!(
+                "unknown stat `{}`; known stats are {} and {}",
+                stat,
+                PERF_STAT_EVENTS,
+                NON_PERF_STATS.join(",")
+            );
+        }
+    }
+    if events.is_empty() {
+        anyhow::bail!("--stats must select at least one perf event (e.g. `instructions:u`)");
+    }
+    Ok(events.join(","))
+}
+
+#[cfg(test)]
+mod perf_stat_selection_tests {
+    use super::narrowed_perf_stat_events;
+
+    #[test]
+    fn narrows_to_requested_events() {
+        assert_eq!(
+            narrowed_perf_stat_events("instructions:u,wall-time").unwrap(),
+            "instructions:u"
+        );
+        assert_eq!(
+            narrowed_perf_stat_events("cycles:u, instructions:u").unwrap(),
+            "cycles:u,instructions:u"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_and_event_free_selections() {
+        assert!(narrowed_perf_stat_events("instruction:u").is_err());
+        assert!(narrowed_perf_stat_events("wall-time").is_err());
+    }
+}
+
+/// The external tools a profiler needs on PATH, beyond the toolchain itself.
+fn required_tools(profiler: Profiler) -> &'static [&'static str] {
+    match profiler {
+        Profiler::SelfProfile => &["summarize", "crox", "flamegraph"],
+        Profiler::SelfProfileJson => &["summarize"],
+        Profiler::PerfRecord | Profiler::PerfStatRecord | Profiler::PerfRecordBolt => &["perf"],
+        Profiler::Oprofile => &["operf"],
+        Profiler::Samply => &["samply"],
+        Profiler::Instruments => &["xcrun"],
+        Profiler::Cachegrind
+        | Profiler::Callgrind
+        | Profiler::CallgrindGraph
+        | Profiler::Dhat
+        | Profiler::DhatCopy
+        | Profiler::Massif
+        | Profiler::ValgrindRaw => &["valgrind"],
+        Profiler::Bytehound => &["bytehound"],
+        Profiler::Eprintln
+        | Profiler::LlvmLines
+        | Profiler::MonoItems
+        | Profiler::DepGraph
+        | Profiler::CrateGraph
+        | Profiler::LlvmIr
+        | Profiler::ArtifactSize => &[],
+    }
+}
+
+/// Checks that the tools and perf events needed for benchmarking (and,
+/// optionally, for the given profilers) are available, and returns a list of
+/// human-readable descriptions of everything that is missing.
+fn check_environment(profilers: &[Profiler]) -> Vec<String> {
+    let mut missing = Vec::new();
+
+    if cfg!(unix) {
+        // `perf stat` reports unavailable events as `<not supported>` or
+        // `<not counted>` rather than failing outright, so run a trivial
+        // command with the full event list and inspect each counter.
+        match Command::new("perf")
+            .args(["stat", "-x;", "-e", PERF_STAT_EVENTS, "--", "true"])
+            .output()
+        {
+            Ok(output) => {
+                // Counter lines are written to stderr.
+                for line in String::from_utf8_lossy(&output.stderr).lines() {
+                    let mut parts = line.split(';');
+                    let cnt = parts.next().unwrap_or_default().trim();
+                    let (_unit, event) = (parts.next(), parts.next().unwrap_or_default().trim());
+                    if cnt == "<not supported>" || cnt == "<not counted>" {
+                        missing.push(format!("perf event `{event}`"));
+                    } else if !event.is_empty() {
+                        println!("ok: perf event `{event}`");
+                    }
+                }
+            }
+            Err(error) => missing.push(format!("`perf` on PATH ({error})")),
+        }
+    }
+
+    let mut tools: Vec<&str> = Vec::new();
+    for profiler in profilers {
+        tools.extend(required_tools(*profiler));
+    }
+    tools.sort_unstable();
+    tools.dedup();
+    for tool in tools {
+        if is_installed(tool) {
+            println!("ok: `{tool}` installed");
+        } else {
+            missing.push(format!("tool `{tool}`"));
+        }
+    }
+
+    missing
+}
+
 #[allow(clippy::too_many_arguments)]
 fn generate_diffs(
     id1: &str,
@@ -199,12 +534,20 @@ fn profile_compile(
     profiles: &[Profile],
     scenarios: &[Scenario],
     backends: &[CodegenBackend],
+    name_template: Option<&str>,
+    layout: OutputLayout,
+    also_measure: bool,
     errors: &mut BenchmarkErrors,
 ) {
     eprintln!("Profiling {} with {:?}", toolchain.id, profiler);
     if let Profiler::SelfProfile = profiler {
         check_measureme_installed().unwrap();
     }
+    if let Profiler::SelfProfileJson = profiler {
+        // Only `summarize` is needed; flamegraph/crox are deliberately not
+        // required for the JSON-only variant.
+        check_summarize_installed().unwrap();
+    }
 
     let error_count: usize = benchmarks
         .par_iter()
@@ -212,14 +555,18 @@ fn profile_compile(
         .map(|(i, benchmark)| {
             let benchmark_id = format!("{} ({}/{})", benchmark.name, i + 1, benchmarks.len());
             eprintln!("Executing benchmark {benchmark_id}");
-            let mut processor = ProfileProcessor::new(profiler, out_dir, &toolchain.id);
+            let mut processor =
+                ProfileProcessor::new(profiler, out_dir, &toolchain.id, name_template, layout);
             let result = wait_for_future(benchmark.measure(
                 &mut processor,
                 profiles,
                 scenarios,
+                None,
                 backends,
                 toolchain,
                 Some(1),
+                0,
+                None,
             ));
             eprintln!("Finished benchmark {benchmark_id}");
 
@@ -228,10 +575,47 @@ fn profile_compile(
                     "collector error: Failed to profile '{}' with {:?}, recorded: {:?}",
                     benchmark.name, profiler, s
                 );
-                1
-            } else {
-                0
+                return 1;
+            }
+
+            // Optionally capture the perf-stat numbers alongside the profiler
+            // artifact, using the same parsing as a normal bench run, and
+            // write them into the output directory as JSON.
+            if also_measure {
+                let mut processor = StatsProcessor::new();
+                let result = wait_for_future(benchmark.measure(
+                    &mut processor,
+                    profiles,
+                    scenarios,
+                    None,
+                    backends,
+                    toolchain,
+                    Some(1),
+                    0,
+                    None,
+                ));
+                if let Err(ref s) = result {
+                    eprintln!(
+                        "collector error: Failed to measure '{}', recorded: {:?}",
+                        benchmark.name, s
+                    );
+                    return 1;
+                }
+                let stats_file =
+                    out_dir.join(format!("{}-{}-stats.json", toolchain.id, benchmark.name));
+                let json = serde_json::to_string_pretty(&processor.into_results())
+                    .expect("serialize stats");
+                if let Err(error) = fs::write(&stats_file, json) {
+                    eprintln!(
+                        "collector error: Failed to write {}: {:?}",
+                        stats_file.display(),
+                        error
+                    );
+                    return 1;
+                }
             }
+
+            0
         })
         .sum();
     errors.add(error_count);
@@ -287,9 +671,136 @@ impl<T: clap::ValueEnum + Sync + Send + 'static> TypedValueParser for EnumArgPar
     }
 }
 
+/// Scenario selection from `--scenarios`. In addition to plain scenario
+/// names this accepts indexed `IncrPatched:N` entries, which restrict
+/// incr-patched runs to the patches with the given indices; a bare
+/// `IncrPatched` (or `All`) keeps running every patch.
+#[derive(Clone, Debug, PartialEq)]
+struct ScenarioSelection {
+    scenarios: Vec<Scenario>,
+    /// `Some` when one or more indexed `IncrPatched:N` entries were given.
+    patch_indices: Option<Vec<usize>>,
+}
+
+impl ScenarioSelection {
+    fn parse(value: &str) -> Result<Self, String> {
+        if value == "All" {
+            return Ok(ScenarioSelection {
+                scenarios: Scenario::all(),
+                patch_indices: None,
+            });
+        }
+
+        let mut scenarios = Vec::new();
+        let mut patch_indices: Option<Vec<usize>> = None;
+        let mut all_patches = false;
+        for item in value.split(',') {
+            let (name, index) = match item.split_once(':') {
+                Some((name, index)) => (name, Some(index)),
+                None => (item, None),
+            };
+            let scenario = <Scenario as clap::ValueEnum>::from_str(name, false)?;
+            match index {
+                Some(index) => {
+                    if scenario != Scenario::IncrPatched {
+                        return Err(format!("`{item}`: only IncrPatched can be indexed"));
+                    }
+                    let index = index
+                        .parse::<usize>()
+                        .map_err(|error| format!("`{item}`: invalid patch index: {error}"))?;
+                    patch_indices.get_or_insert_with(Vec::new).push(index);
+                }
+                None => all_patches |= scenario == Scenario::IncrPatched,
+            }
+            if !scenarios.contains(&scenario) {
+                scenarios.push(scenario);
+            }
+        }
+        Ok(ScenarioSelection {
+            scenarios,
+            // A bare `IncrPatched` runs every patch and overrides any indexed
+            // entries.
+            patch_indices: if all_patches { None } else { patch_indices },
+        })
+    }
+}
+
+#[derive(Clone, Default)]
+struct ScenarioArgParser;
+
+impl TypedValueParser for ScenarioArgParser {
+    type Value = ScenarioSelection;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        _arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        ScenarioSelection::parse(value.to_str().unwrap()).map_err(|message| {
+            clap::Error::raw(clap::error::ErrorKind::InvalidValue, message).with_cmd(cmd)
+        })
+    }
+}
+
+#[cfg(test)]
+mod scenario_selection_tests {
+    use super::{Scenario, ScenarioSelection};
+
+    #[test]
+    fn parses_indexed_incr_patched() {
+        let selection = ScenarioSelection::parse("IncrPatched:0,IncrPatched:2").unwrap();
+        assert_eq!(selection.scenarios, vec![Scenario::IncrPatched]);
+        assert_eq!(selection.patch_indices, Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn bare_incr_patched_runs_all_patches() {
+        let selection = ScenarioSelection::parse("Full,IncrPatched").unwrap();
+        assert_eq!(
+            selection.scenarios,
+            vec![Scenario::Full, Scenario::IncrPatched]
+        );
+        assert_eq!(selection.patch_indices, None);
+    }
+
+    #[test]
+    fn rejects_indexing_other_scenarios() {
+        assert!(ScenarioSelection::parse("Full:1").is_err());
+    }
+}
+
+#[cfg(test)]
+mod glob_match_tests {
+    use super::glob_match;
+
+    #[test]
+    fn wildcards_match_substrings() {
+        assert!(glob_match("serde*", "serde-1.0.219"));
+        assert!(glob_match("*-opt", "cargo-opt"));
+        assert!(glob_match("a*c*e", "abcde"));
+        assert!(glob_match("*", "anything"));
+    }
+
+    #[test]
+    fn literal_patterns_match_exactly() {
+        assert!(glob_match("ripgrep", "ripgrep"));
+        assert!(!glob_match("ripgrep", "ripgrep-14"));
+        assert!(!glob_match("serde*", "rust-serde"));
+    }
+}
+
 #[derive(Debug, clap::Parser)]
 #[command(about, version, author)]
 struct Cli {
+    /// Directory containing the compile-time benchmarks. Defaults to
+    /// `collector/compile-benchmarks`, which assumes the collector is run
+    /// from the repository root; set this to run from elsewhere or to use an
+    /// external benchmark suite. Relative paths are resolved against the
+    /// current directory.
+    #[arg(long, global = true)]
+    benchmark_dir: Option<PathBuf>,
+
     #[clap(subcommand)]
     command: Commands,
 }
@@ -350,14 +861,15 @@ struct CompileTimeOptions {
     )]
     profiles: MultiEnumValue<Profile>,
 
-    /// Measure the scenarios in this comma-separated list
+    /// Measure the scenarios in this comma-separated list. `IncrPatched` can
+    /// be restricted to individual patches with `IncrPatched:<index>`.
     #[arg(
         long = "scenarios",
         alias = "runs", // the old name, for backward compatibility
-        value_parser = EnumArgParser::<Scenario>::default(),
+        value_parser = ScenarioArgParser,
         default_value = "All"
     )]
-    scenarios: MultiEnumValue<Scenario>,
+    scenarios: ScenarioSelection,
 
     /// Measure the codegen backends in this comma-separated list
     #[arg(long = "backends", value_parser = EnumArgParser::<CodegenBackend>::default(), default_value = "Llvm")]
@@ -416,6 +928,11 @@ struct PurgeOption {
     /// Removes old data for the specified artifact prior to running the benchmarks.
     #[arg(long = "purge")]
     purge: Option<PurgeMode>,
+
+    /// Only print a summary of what `--purge` would remove, without removing
+    /// anything.
+    #[arg(long = "purge-dry-run", requires = "purge")]
+    purge_dry_run: bool,
 }
 
 #[derive(Debug, clap::Args)]
@@ -555,6 +1072,81 @@ enum Commands {
         #[arg(long, default_value = "1")]
         iterations: usize,
 
+        /// The number of iterations to execute and discard before the measured
+        /// iterations, to warm up caches and stabilize CPU frequency. Warmup
+        /// iterations still pay the full build cost.
+        #[arg(long, default_value = "0")]
+        warmup: usize,
+
+        /// Cap on the number of cores used by the (untimed) preparation phase
+        /// of each benchmark. Defaults to all available cores. Timed
+        /// measurement iterations are never parallelized, as overlapping them
+        /// would pollute each other's hardware counters.
+        #[arg(long)]
+        parallel: Option<usize>,
+
+        /// Minimum number of gigabytes that must be free on the temp
+        /// filesystem before benchmarking starts. Benchmarks repeatedly copy
+        /// whole source trees into temp dirs, and running out of disk midway
+        /// surfaces as cryptic cargo errors. Pass 0 to disable the check.
+        #[arg(long, default_value = "5")]
+        min_free_disk_gb: u64,
+
+        /// Maximum number of seconds a single rustc invocation may run before
+        /// it is killed and the benchmark is recorded as errored. Defaults to
+        /// 30 minutes; only meant as a guard against completely hung builds.
+        #[arg(long)]
+        build_timeout: Option<u64>,
+
+        /// Replaces results previously recorded under the same `--id` instead
+        /// of appending a new timestamped entry. The artifact date is derived
+        /// deterministically from the id, so repeated runs map to the same
+        /// artifact, and any data already stored for it is purged first.
+        #[arg(long)]
+        overwrite: bool,
+
+        /// Re-runs benchmarks that errored in a previous run for this
+        /// artifact, replacing the stored error with the new result.
+        /// Benchmarks that already succeeded are still skipped.
+        #[arg(long)]
+        rerun_errors: bool,
+
+        /// How many times a flaky measurement is retried before the
+        /// benchmark is recorded as errored.
+        #[arg(long, default_value_t = DEFAULT_MAX_RETRIES)]
+        max_retries: u8,
+
+        /// Prints every cargo invocation that would be run (with its
+        /// environment) instead of executing it. Nothing is measured.
+        #[arg(long)]
+        dump_commands: bool,
+
+        /// Benchmarks in a seeded random order instead of sorted by name, to
+        /// decorrelate benchmark identity from machine thermal state. Without
+        /// a seed, one is chosen and printed so the order can be reproduced.
+        #[arg(long, value_name = "SEED", num_args = 0..=1)]
+        shuffle: Option<Option<u64>>,
+
+        /// For IncrUnchanged-only runs, populate the incremental cache once
+        /// (with a discarded IncrFull build) and reuse it across iterations,
+        /// instead of paying for a fresh IncrFull before every measurement.
+        #[arg(long)]
+        reuse_incremental_cache: bool,
+
+        /// Emits one JSON object per benchmarking event (benchmark
+        /// started/finished, overall progress) to stdout, for supervising
+        /// processes. Human-readable output stays on stderr.
+        #[arg(long)]
+        progress: bool,
+
+        /// Comma-separated list of stats to record, narrowing the events
+        /// requested from `perf stat` (e.g. `instructions:u,wall-time`).
+        /// Useful for quick local iteration where most counters are noise.
+        /// The default records the full event set; `wall-time` and `max-rss`
+        /// are always recorded regardless of this selection.
+        #[arg(long)]
+        stats: Option<String>,
+
         #[command(flatten)]
         self_profile: SelfProfileOption,
 
@@ -575,6 +1167,25 @@ enum Commands {
 
         #[command(flatten)]
         self_profile: SelfProfileOption,
+
+        /// Do not reuse previously downloaded sysroots; extract from scratch
+        /// and remove the sysroot again after benchmarking.
+        #[arg(long)]
+        no_sysroot_cache: bool,
+
+        /// Emits one JSON object per benchmarking event (benchmark
+        /// started/finished, overall progress) to stdout, for supervising
+        /// processes. Human-readable output stays on stderr.
+        #[arg(long)]
+        progress: bool,
+
+        /// Stop at the first benchmark that errors. By default errors are
+        /// recorded per benchmark and collection continues, so one bad
+        /// benchmark does not abort an unattended run. In both modes the
+        /// process exits non-zero if any benchmark failed; with this flag it
+        /// does so immediately after the first failure.
+        #[arg(long)]
+        fail_fast: bool,
     },
 
     /// Benchmarks a published toolchain for perf.rust-lang.org's dashboard
@@ -586,6 +1197,20 @@ enum Commands {
         db: DbOption,
     },
 
+    /// Checks that this machine is ready to run benchmarks: verifies that the
+    /// perf events used for measurements are actually supported and that the
+    /// tools needed by the requested profilers are installed. Exits with a
+    /// non-zero code if anything is missing, so CI can gate on it.
+    CheckEnv {
+        /// Also check the tools needed by the given profilers.
+        #[arg(long, value_parser = EnumArgParser::<Profiler>::default())]
+        profilers: Option<MultiEnumValue<Profiler>>,
+    },
+
+    /// Lists the supported profilers, the scenarios each one can profile, and
+    /// whether its backing tools are present on PATH.
+    ListProfilers,
+
     /// Profiles a local rustc with one of several profilers
     ProfileLocal {
         /// Profiler to use
@@ -612,6 +1237,35 @@ enum Commands {
         /// This flag is only supported for certain profilers
         #[arg(long, short = 'j', default_value = "1")]
         jobs: u64,
+
+        /// Roots timing directories at deterministic paths, so that file
+        /// paths baked into profiler output (e.g. cachegrind annotations)
+        /// are comparable between runs.
+        #[arg(long)]
+        deterministic_dirs: bool,
+
+        /// Template for output filenames. Supports the placeholders
+        /// `{prefix}`, `{id}`, `{benchmark}`, `{profile}`, `{scenario}` and
+        /// `{ts}`; defaults to `{prefix}-{id}-{benchmark}-{profile}-{scenario}`.
+        #[arg(long)]
+        name_template: Option<String>,
+
+        /// How output files are arranged: `flat` (everything in the output
+        /// directory, long hyphenated names) or `nested`
+        /// (`<id>/<benchmark>/<profile>/<scenario>/` with short names).
+        #[arg(long, value_enum, default_value_t = OutputLayout::Flat)]
+        layout: OutputLayout,
+
+        /// Prints every cargo invocation that would be run (with its
+        /// environment) instead of executing it. Nothing is profiled.
+        #[arg(long)]
+        dump_commands: bool,
+
+        /// Additionally runs each benchmark once under `perf stat` and writes
+        /// the resulting statistics into the output directory as
+        /// `<id>-<benchmark>-stats.json`.
+        #[arg(long)]
+        also_measure: bool,
     },
 
     /// Installs the next commit for perf.rust-lang.org
@@ -633,6 +1287,19 @@ enum Commands {
         db: DbOption,
     },
 
+    /// Removes one or more benchmarks and all their stored results.
+    PurgeBenchmark {
+        /// Name of the benchmark, or a glob (`serde*`) matching several.
+        benchmark: String,
+
+        /// Only report what would be removed, without touching the database.
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        db: DbOption,
+    },
+
     /// Displays diff between two local bench results.
     BenchCmp {
         #[command(flatten)]
@@ -647,6 +1314,10 @@ enum Commands {
 
         /// The name of the modified artifact to be compared.
         modified: Option<String>,
+
+        /// Only list per-benchmark changes of at least this many percent.
+        #[arg(long, default_value_t = 1.0)]
+        threshold: f64,
     },
 }
 
@@ -686,10 +1357,22 @@ enum DownloadSubcommand {
 
 fn main_result() -> anyhow::Result<i32> {
     env_logger::init();
+    install_stop_handler();
 
     let args = Cli::parse();
 
-    let compile_benchmark_dir = compile_benchmark_dir();
+    let compile_benchmark_dir = match &args.benchmark_dir {
+        Some(dir) => {
+            if !dir.is_dir() {
+                anyhow::bail!(
+                    "benchmark directory `{}` does not exist or is not a directory",
+                    dir.display()
+                );
+            }
+            dir.clone()
+        }
+        None => compile_benchmark_dir(),
+    };
     let runtime_benchmark_dir = runtime_benchmark_dir();
 
     let benchmark_dirs = BenchmarkDirs {
@@ -738,9 +1421,16 @@ fn main_result() -> anyhow::Result<i32> {
                 sha: toolchain.id.clone(),
                 date: Utc::now().into(),
                 r#type: CommitType::Master,
+                message: None,
+                pr: None,
             });
 
-            rt.block_on(purge_old_data(conn.as_mut(), &artifact_id, purge.purge));
+            rt.block_on(purge_old_data(
+                conn.as_mut(),
+                &artifact_id,
+                purge.purge,
+                purge.purge_dry_run,
+            ));
 
             let runtime_suite = rt.block_on(load_runtime_benchmarks(
                 conn.as_mut(),
@@ -857,12 +1547,45 @@ fn main_result() -> anyhow::Result<i32> {
             db,
             bench_rustc,
             iterations,
+            warmup,
+            parallel,
+            min_free_disk_gb,
+            build_timeout,
+            overwrite,
+            rerun_errors,
+            max_retries,
+            dump_commands,
+            shuffle,
+            reuse_incremental_cache,
+            progress,
+            stats,
             self_profile,
             purge,
         } => {
+            if progress {
+                enable_progress_events();
+            }
             log_db(&db);
+            check_free_disk_space(min_free_disk_gb)?;
+            if let Some(seconds) = build_timeout {
+                collector::compile::execute::set_build_timeout(Duration::from_secs(seconds));
+            }
+            if dump_commands {
+                collector::compile::execute::dump_commands();
+            }
+            if reuse_incremental_cache {
+                collector::compile::benchmark::reuse_incremental_cache();
+            }
+            if let Some(stats) = &stats {
+                let events = narrowed_perf_stat_events(stats)?;
+                // Inherited by the rustc-fake processes the benchmarks spawn.
+                std::env::set_var("RUSTC_PERF_STAT_EVENTS", events);
+            }
             let profiles = opts.profiles.0;
-            let scenarios = opts.scenarios.0;
+            let ScenarioSelection {
+                scenarios,
+                patch_indices,
+            } = opts.scenarios;
             let backends = opts.codegen_backends.0;
 
             let pool = database::Pool::open(&db.db);
@@ -887,16 +1610,41 @@ fn main_result() -> anyhow::Result<i32> {
                 &local.exclude_suffix,
             )?;
             benchmarks.retain(|b| local.category.0.contains(&b.category()));
+            if let Some(seed) = shuffle {
+                let seed = seed.unwrap_or_else(generate_shuffle_seed);
+                println!("shuffling benchmark order with seed {seed}");
+                shuffle_benchmarks(&mut benchmarks, seed);
+            }
 
+            let date = if overwrite {
+                deterministic_commit_date(&toolchain.id)
+            } else {
+                Utc::now()
+            };
             let artifact_id = ArtifactId::Commit(Commit {
                 sha: toolchain.id.clone(),
-                date: Utc::now().into(),
+                date: date.into(),
                 r#type: CommitType::Master,
+                message: None,
+                pr: None,
             });
 
+            // Overwriting means replacing whatever was stored for this
+            // artifact in a previous run.
+            let purge_mode = if overwrite {
+                purge.purge.or(Some(PurgeMode::Old))
+            } else {
+                purge.purge
+            };
+
             let mut rt = build_async_runtime();
             let mut conn = rt.block_on(pool.connection());
-            rt.block_on(purge_old_data(conn.as_mut(), &artifact_id, purge.purge));
+            rt.block_on(purge_old_data(
+                conn.as_mut(),
+                &artifact_id,
+                purge_mode,
+                purge.purge_dry_run,
+            ));
 
             let shared = SharedBenchmarkConfig {
                 toolchain,
@@ -906,10 +1654,16 @@ fn main_result() -> anyhow::Result<i32> {
                 benchmarks,
                 profiles,
                 scenarios,
+                patch_indices,
                 backends,
                 iterations: Some(iterations),
+                warmup,
+                prep_parallelism: parallel,
                 is_self_profile: self_profile.self_profile,
                 bench_rustc: bench_rustc.bench_rustc,
+                rerun_errored: rerun_errors,
+                max_retries,
+                fail_fast: false,
             };
 
             run_benchmarks(&mut rt, conn, shared, Some(config), None)?;
@@ -921,8 +1675,17 @@ fn main_result() -> anyhow::Result<i32> {
             db,
             bench_rustc,
             self_profile,
+            no_sysroot_cache,
+            progress,
+            fail_fast,
         } => {
             log_db(&db);
+            if no_sysroot_cache {
+                collector::toolchain::disable_sysroot_cache();
+            }
+            if progress {
+                enable_progress_events();
+            }
             println!("processing artifacts");
             let client = reqwest::blocking::Client::new();
             let response: collector::api::next_artifact::Response = client
@@ -1020,10 +1783,16 @@ fn main_result() -> anyhow::Result<i32> {
                                 Profile::Opt,
                             ],
                             scenarios: Scenario::all(),
+                            patch_indices: None,
                             backends,
                             iterations: runs.map(|v| v as usize),
+                            warmup: 0,
+                            prep_parallelism: None,
                             is_self_profile: self_profile.self_profile,
                             bench_rustc: bench_rustc.bench_rustc,
+                            rerun_errored: false,
+                            max_retries: DEFAULT_MAX_RETRIES,
+                            fail_fast,
                         };
                         let runtime_suite = rt.block_on(load_runtime_benchmarks(
                             conn.as_mut(),
@@ -1076,6 +1845,62 @@ fn main_result() -> anyhow::Result<i32> {
             Ok(0)
         }
 
+        Commands::CheckEnv { profilers } => {
+            let profilers = profilers.map(|p| p.0).unwrap_or_default();
+            let missing = check_environment(&profilers);
+            if missing.is_empty() {
+                println!("Environment is ready for benchmarking");
+                Ok(0)
+            } else {
+                for item in &missing {
+                    eprintln!("missing: {item}");
+                }
+                eprintln!("{} requirement(s) not satisfied", missing.len());
+                Ok(1)
+            }
+        }
+
+        Commands::ListProfilers => {
+            use clap::ValueEnum;
+            for profiler in Profiler::value_variants() {
+                let name = profiler
+                    .to_possible_value()
+                    .expect("every profiler has a CLI name")
+                    .get_name()
+                    .to_string();
+                let scenarios = Scenario::all()
+                    .into_iter()
+                    .filter(|scenario| {
+                        collector::compile::execute::PerfTool::ProfileTool(*profiler)
+                            .is_scenario_allowed(*scenario)
+                    })
+                    .map(|scenario| format!("{scenario:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!("{name}");
+                println!("  scenarios: {scenarios}");
+                let tools = required_tools(*profiler);
+                if tools.is_empty() {
+                    println!("  requires: no external tools");
+                } else {
+                    let tools = tools
+                        .iter()
+                        .map(|tool| {
+                            let status = if is_installed(tool) {
+                                "installed"
+                            } else {
+                                "MISSING"
+                            };
+                            format!("{tool} ({status})")
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    println!("  requires: {tools}");
+                }
+            }
+            Ok(0)
+        }
+
         Commands::ProfileLocal {
             profiler,
             local,
@@ -1083,7 +1908,26 @@ fn main_result() -> anyhow::Result<i32> {
             out_dir,
             rustc2,
             jobs,
+            deterministic_dirs,
+            name_template,
+            layout,
+            dump_commands,
+            also_measure,
         } => {
+            if let Some(template) = &name_template {
+                validate_name_template(template)?;
+            }
+            if layout == OutputLayout::Nested && rustc2.is_some() {
+                // The diffing machinery locates its inputs by the flat
+                // filename scheme.
+                anyhow::bail!("--layout nested cannot be combined with --rustc2 diffing");
+            }
+            if deterministic_dirs {
+                collector::compile::benchmark::use_deterministic_temp_dirs();
+            }
+            if dump_commands {
+                collector::compile::execute::dump_commands();
+            }
             let jobs = jobs.max(1);
             if jobs > 1 && !profiler.supports_parallel_execution() {
                 anyhow::bail!(
@@ -1093,7 +1937,7 @@ fn main_result() -> anyhow::Result<i32> {
             }
 
             let profiles = &opts.profiles.0;
-            let scenarios = &opts.scenarios.0;
+            let scenarios = &opts.scenarios.scenarios;
             let backends = &opts.codegen_backends.0;
 
             let mut benchmarks = get_compile_benchmarks(
@@ -1135,6 +1979,9 @@ fn main_result() -> anyhow::Result<i32> {
                         profiles,
                         scenarios,
                         backends,
+                        name_template.as_deref(),
+                        layout,
+                        also_measure,
                         &mut errors,
                     );
                     Ok(id)
@@ -1227,16 +2074,56 @@ Make sure to modify `{dir}/perf-config.json` if the category/artifact don't matc
             println!("Data of artifact {name} were removed");
             Ok(0)
         }
+        Commands::PurgeBenchmark {
+            benchmark: pattern,
+            dry_run,
+            db,
+        } => {
+            let pool = Pool::open(&db.db);
+            let rt = build_async_runtime();
+            let conn = rt.block_on(pool.connection());
+
+            let matching: Vec<String> = rt
+                .block_on(conn.get_compile_benchmarks())
+                .into_iter()
+                .map(|benchmark| benchmark.name)
+                .filter(|name| glob_match(&pattern, name))
+                .collect();
+            if matching.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "no benchmark in the database matches `{pattern}`"
+                ));
+            }
+
+            for name in &matching {
+                let (artifacts, results) = rt.block_on(conn.compile_benchmark_scope(name));
+                if dry_run {
+                    println!(
+                        "would remove {name}: {results} result(s) across {artifacts} artifact(s)"
+                    );
+                } else {
+                    rt.block_on(conn.purge_compile_benchmark(name));
+                    println!("removed {name}: {results} result(s) across {artifacts} artifact(s)");
+                }
+            }
+            println!(
+                "{} {} benchmark(s)",
+                if dry_run { "would remove" } else { "removed" },
+                matching.len()
+            );
+            Ok(0)
+        }
         Commands::BenchCmp {
             db,
             base,
             modified,
             metric,
+            threshold,
         } => {
             let pool = Pool::open(&db.db);
             let rt = build_async_runtime();
             let conn = rt.block_on(pool.connection());
-            rt.block_on(compare_artifacts(conn, metric, base, modified))?;
+            rt.block_on(compare_artifacts(conn, metric, base, modified, threshold))?;
             Ok(0)
         }
     }
@@ -1594,19 +2481,40 @@ async fn purge_old_data(
     conn: &mut dyn Connection,
     artifact_id: &ArtifactId,
     purge_mode: Option<PurgeMode>,
+    dry_run: bool,
 ) {
     match purge_mode {
         Some(PurgeMode::Old) => {
+            if dry_run {
+                eprintln!("would purge all data of artifact {artifact_id}");
+                return;
+            }
             // Delete everything associated with the artifact
             conn.purge_artifact(artifact_id).await;
+            eprintln!("purged all data of artifact {artifact_id}");
         }
         Some(PurgeMode::Failed) => {
             // Delete all benchmarks that have an error for the given artifact
             let artifact_row_id = conn.artifact_id(artifact_id).await;
             let errors = conn.get_error(artifact_row_id).await;
-            for krate in errors.keys() {
+            let mut benchmarks = errors.keys().cloned().collect::<Vec<_>>();
+            benchmarks.sort();
+            if dry_run {
+                eprintln!(
+                    "would purge {} errored benchmark(s) of artifact {artifact_id}: {}",
+                    benchmarks.len(),
+                    benchmarks.join(", ")
+                );
+                return;
+            }
+            for krate in &benchmarks {
                 conn.collector_remove_step(artifact_row_id, krate).await;
             }
+            eprintln!(
+                "purged {} errored benchmark(s) of artifact {artifact_id}: {}",
+                benchmarks.len(),
+                benchmarks.join(", ")
+            );
         }
         None => {}
     }
@@ -1653,6 +2561,12 @@ fn run_benchmarks(
         runtime.as_ref(),
     ));
 
+    let fingerprint = gather_environment_fingerprint(&shared.toolchain.triple);
+    rt.block_on(connection.record_environment(
+        collector.artifact_row_id,
+        &serde_json::to_string(&fingerprint).unwrap(),
+    ));
+
     let start = Instant::now();
 
     // Compile benchmarks
@@ -1730,10 +2644,16 @@ fn bench_published_artifact(
             benchmarks: compile_benchmarks,
             profiles,
             scenarios,
+            patch_indices: None,
             backends: vec![CodegenBackend::Llvm],
             iterations: Some(3),
+            warmup: 0,
+            prep_parallelism: None,
             is_self_profile: false,
             bench_rustc: false,
+            rerun_errored: false,
+            max_retries: DEFAULT_MAX_RETRIES,
+            fail_fast: false,
         }),
         Some(RuntimeBenchmarkConfig::new(
             runtime_suite,
@@ -1764,60 +2684,100 @@ fn bench_compile(
     collector: &CollectorCtx,
 ) -> BenchmarkErrors {
     let mut errors = BenchmarkErrors::new();
+    let artifact_name = match &shared.artifact_id {
+        ArtifactId::Commit(commit) => format!("{} ({})", commit.short_sha(), commit.date),
+        ArtifactId::Tag(tag) => tag.clone(),
+    };
     eprintln!(
         "Benchmarking {} for triple {}",
-        shared.artifact_id, shared.toolchain.triple
+        artifact_name, shared.toolchain.triple
     );
 
     let bench_rustc = config.bench_rustc;
 
     let start = Instant::now();
 
-    let mut measure_and_record =
-        |benchmark_name: &BenchmarkName,
-         category: Category,
-         print_intro: &dyn Fn(),
-         measure: &dyn Fn(&mut BenchProcessor) -> anyhow::Result<()>| {
-            let is_fresh = rt.block_on(collector.start_compile_step(conn, benchmark_name));
-            if !is_fresh {
+    // Returns whether the benchmark errored, so that the caller can honor
+    // `--fail-fast`.
+    let mut measure_and_record = |benchmark_name: &BenchmarkName,
+                                  category: Category,
+                                  print_intro: &dyn Fn(),
+                                  measure: &dyn Fn(&mut BenchProcessor) -> anyhow::Result<()>|
+     -> bool {
+        let is_fresh = rt.block_on(collector.start_compile_step(conn, benchmark_name));
+        if !is_fresh {
+            let previously_errored = config.rerun_errored
+                && rt
+                    .block_on(conn.get_error(collector.artifact_row_id))
+                    .contains_key(&benchmark_name.0);
+            if !previously_errored {
                 eprintln!("skipping {} -- already benchmarked", benchmark_name);
-                return;
+                return false;
             }
-            let mut tx = rt.block_on(conn.transaction());
-            let (supports_stable, category) = category.db_representation();
-            rt.block_on(tx.conn().record_compile_benchmark(
+            eprintln!("re-running {} -- previous run errored", benchmark_name);
+        }
+        let mut tx = rt.block_on(conn.transaction());
+        if !is_fresh {
+            // Drop the stale error so the new result replaces it.
+            rt.block_on(
+                tx.conn()
+                    .clear_error(collector.artifact_row_id, &benchmark_name.0),
+            );
+        }
+        let (supports_stable, category) = category.db_representation();
+        rt.block_on(tx.conn().record_compile_benchmark(
+            &benchmark_name.0,
+            Some(supports_stable),
+            category,
+        ));
+        print_intro();
+        emit_progress(serde_json::json!({
+            "event": "benchmark-started",
+            "benchmark": benchmark_name.0,
+        }));
+        let mut processor = BenchProcessor::new(
+            tx.conn(),
+            benchmark_name,
+            &shared.artifact_id,
+            collector.artifact_row_id,
+            config.is_self_profile,
+            config.max_retries,
+        );
+        let result = measure(&mut processor);
+        emit_progress(serde_json::json!({
+            "event": "benchmark-finished",
+            "benchmark": benchmark_name.0,
+            "ok": result.is_ok(),
+        }));
+        let errored = if let Err(s) = result {
+            eprintln!(
+                "collector error: Failed to benchmark '{}', recorded: {:#}",
+                benchmark_name, s
+            );
+            errors.incr();
+            let benchmark_error = BenchmarkError::classify(&s);
+            rt.block_on(tx.conn().record_error(
+                collector.artifact_row_id,
                 &benchmark_name.0,
-                Some(supports_stable),
-                category,
+                &serde_json::to_string(&benchmark_error).unwrap(),
             ));
-            print_intro();
-            let mut processor = BenchProcessor::new(
-                tx.conn(),
-                benchmark_name,
-                &shared.artifact_id,
-                collector.artifact_row_id,
-                config.is_self_profile,
-            );
-            let result = measure(&mut processor);
-            if let Err(s) = result {
-                eprintln!(
-                    "collector error: Failed to benchmark '{}', recorded: {:#}",
-                    benchmark_name, s
-                );
-                errors.incr();
-                rt.block_on(tx.conn().record_error(
-                    collector.artifact_row_id,
-                    &benchmark_name.0,
-                    &format!("{:?}", s),
-                ));
-            };
-            rt.block_on(collector.end_compile_step(tx.conn(), benchmark_name));
-            rt.block_on(tx.commit()).expect("committed");
+            true
+        } else {
+            false
         };
+        rt.block_on(collector.end_compile_step(tx.conn(), benchmark_name));
+        rt.block_on(tx.commit()).expect("committed");
+        errored
+    };
 
     // Normal benchmarks.
+    let mut aborted = false;
     for (nth_benchmark, benchmark) in config.benchmarks.iter().enumerate() {
-        measure_and_record(
+        if collector::stop_requested() {
+            eprintln!("Stop requested; results recorded so far have been kept");
+            break;
+        }
+        let errored = measure_and_record(
             &benchmark.name,
             benchmark.category(),
             &|| {
@@ -1831,17 +2791,33 @@ fn bench_compile(
                     processor,
                     &config.profiles,
                     &config.scenarios,
+                    config.patch_indices.as_deref(),
                     &config.backends,
                     &shared.toolchain,
                     config.iterations,
+                    config.warmup,
+                    config.prep_parallelism,
                 )))
                 .with_context(|| anyhow::anyhow!("Cannot compile {}", benchmark.name))
             },
-        )
+        );
+        emit_progress(serde_json::json!({
+            "event": "progress",
+            "done": nth_benchmark + 1,
+            "total": config.benchmarks.len(),
+        }));
+        if errored && config.fail_fast {
+            eprintln!(
+                "fail-fast: aborting collection after error in {}",
+                benchmark.name
+            );
+            aborted = true;
+            break;
+        }
     }
 
     // The special rustc benchmark, if requested.
-    if bench_rustc {
+    if bench_rustc && !aborted {
         measure_and_record(
             &BenchmarkName("rustc".to_string()),
             Category::Primary,
@@ -2030,6 +3006,8 @@ fn get_commit_or_fake_it(sha: &str) -> anyhow::Result<Commit> {
             sha: c.sha.as_str().into(),
             date: c.time.into(),
             r#type: CommitType::Master,
+            message: None,
+            pr: c.pr,
         })
         .unwrap_or_else(|| {
             log::warn!("utilizing fake commit!");
@@ -2037,6 +3015,8 @@ fn get_commit_or_fake_it(sha: &str) -> anyhow::Result<Commit> {
                 sha: sha.into(),
                 date: database::Date::ymd_hms(2000, 1, 1, 0, 0, 0),
                 r#type: CommitType::Master,
+                message: None,
+                pr: None,
             }
         }))
 }
@@ -5,6 +5,19 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+/// The default `perf stat` event list. Keep this in sync with
+/// `PERF_STAT_EVENTS` in the collector binary.
+const DEFAULT_PERF_STAT_EVENTS: &str =
+    "instructions:u,cycles:u,task-clock,cpu-clock,faults,context-switches,branch-misses,cache-misses";
+
+/// The events to request from `perf stat`. The collector's `--stats` flag
+/// narrows the default list by setting `RUSTC_PERF_STAT_EVENTS` (to a
+/// validated subset), e.g. for quick local runs that only need
+/// `instructions:u`.
+fn perf_stat_events() -> String {
+    env::var("RUSTC_PERF_STAT_EVENTS").unwrap_or_else(|_| DEFAULT_PERF_STAT_EVENTS.to_string())
+}
+
 fn determinism_env(cmd: &mut Command) {
     // Since rust-lang/rust#89836, rustc stable crate IDs include a hash of the
     // rustc version (including the git commit it's built from), which means
@@ -27,7 +40,13 @@ fn determinism_env(cmd: &mut Command) {
 
 fn run_with_determinism_env(mut cmd: Command) {
     determinism_env(&mut cmd);
-    let status = cmd.status().expect("failed to spawn");
+    let mut child = cmd.spawn().expect("failed to spawn");
+    let status = child.wait().expect("failed to wait");
+    // The process has exited, but its handle is still open, which lets us
+    // query its peak memory usage on Windows (unix uses
+    // `getrusage(RUSAGE_CHILDREN)` instead, see `print_memory`).
+    #[cfg(windows)]
+    record_child_peak_memory(&child);
     assert!(
         status.success(),
         "command did not complete successfully: {:?}",
@@ -49,7 +68,62 @@ fn create_self_profile_dir() -> PathBuf {
     std::env::current_dir().unwrap().join(name)
 }
 
+/// Parses a cpuset list such as `2-3` or `0,2,4-6` into individual CPU ids.
+#[cfg(target_os = "linux")]
+fn parse_cpuset(s: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in s.split(',') {
+        let part = part.trim();
+        if let Some((start, end)) = part.split_once('-') {
+            let start: usize = start.parse().ok()?;
+            let end: usize = end.parse().ok()?;
+            if start > end {
+                return None;
+            }
+            cpus.extend(start..=end);
+        } else {
+            cpus.push(part.parse().ok()?);
+        }
+    }
+    if cpus.is_empty() {
+        None
+    } else {
+        Some(cpus)
+    }
+}
+
+/// Pins this process to the CPUs named by `RUSTC_PERF_CPUSET` (e.g. `2-3`);
+/// the spawned rustc/profiler children inherit the affinity. Pairs with
+/// `isolcpus`-style kernel boot options to keep benchmarks off busy cores.
+/// Linux-only; the variable is ignored on other platforms.
+#[cfg(target_os = "linux")]
+fn apply_cpu_affinity() {
+    let cpuset = match env::var("RUSTC_PERF_CPUSET") {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+    let cpus =
+        parse_cpuset(&cpuset).unwrap_or_else(|| panic!("invalid RUSTC_PERF_CPUSET `{}`", cpuset));
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        let r = libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set);
+        assert_eq!(
+            r, 0,
+            "sched_setaffinity failed for RUSTC_PERF_CPUSET `{}`",
+            cpuset
+        );
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn apply_cpu_affinity() {}
+
 fn main() {
+    apply_cpu_affinity();
+
     let mut args_os = env::args_os();
     let name = args_os.next().unwrap().into_string().unwrap();
 
@@ -85,29 +159,52 @@ fn main() {
     // verification.
     args.push(OsString::from("-Zincremental-verify-ich"));
 
-    if let Some(pos) = args.iter().position(|arg| arg == "--wrap-rustc-with") {
+    let arg_wrapper = if let Some(pos) = args.iter().position(|arg| arg == "--wrap-rustc-with") {
         // Strip out the flag and its argument, and run rustc under the wrapper
         // program named by the argument.
         args.remove(pos);
         let wrapper = args.remove(pos);
-        let wrapper = wrapper.to_str().unwrap();
+        Some(wrapper.to_str().unwrap().to_string())
+    } else {
+        None
+    };
+    // Whole-workspace benchmarks cannot reach individual rustc invocations
+    // through `cargo rustc`'s trailing args, so the wrapper name may also
+    // arrive via the environment. Version and metadata queries (`-vV`,
+    // `--print=...`) must still run unwrapped.
+    let env_wrapper = env::var("WRAP_RUSTC_WITH").ok().filter(|_| {
+        !args
+            .iter()
+            .any(|arg| arg == "-vV" || arg.to_str().is_some_and(|a| a.starts_with("--print")))
+    });
+
+    if let Some(wrapper) = arg_wrapper.or(env_wrapper) {
+        let wrapper = wrapper.as_str();
 
         benchlib::process::raise_process_priority();
 
         // These strings come from `PerfTool::name()`.
         match wrapper {
             "PerfStat" | "PerfStatSelfProfile" => {
-                let mut cmd = Command::new("perf");
+                let mut cmd = Command::new(tool_binary("PERF_BIN", "perf"));
                 let has_perf = cmd.output().is_ok();
                 assert!(has_perf);
                 cmd.arg("stat")
                     // perf respects this environment variable for e.g., percents in
                     // the output, but we want standard output on all systems.
                     // See #753 for more details.
-                    .env("LC_NUMERIC", "C")
-                    .arg("-x;")
-                    .arg("-e")
-                    .arg("instructions:u,cycles:u,task-clock,cpu-clock,faults,context-switches,branch-misses,cache-misses")
+                    .env("LC_NUMERIC", "C");
+                // Newer perf versions can emit JSON (`-j`), which is more
+                // robust against locale/format drift than the `-x;` CSV
+                // format. Opt-in via environment variable so that old perf
+                // versions keep working.
+                if env::var_os("RUSTC_PERF_STAT_JSON").is_some() {
+                    cmd.arg("-j");
+                } else {
+                    cmd.arg("-x;");
+                }
+                cmd.arg("-e")
+                    .arg(perf_stat_events())
                     .arg("--log-fd")
                     .arg("1")
                     .arg("setarch")
@@ -194,6 +291,7 @@ fn main() {
                 let start = Instant::now();
                 run_with_determinism_env(tool);
                 let dur = start.elapsed();
+                print_memory();
                 println!("!wall-time:{}.{:09}", dur.as_secs(), dur.subsec_nanos());
 
                 let xperf = |args: &[&str]| {
@@ -214,7 +312,7 @@ fn main() {
                 }
             }
 
-            "SelfProfile" => {
+            "SelfProfile" | "SelfProfileJson" => {
                 let mut cmd = Command::new(&tool);
                 cmd.arg("-Zself-profile-events=all")
                     .arg("-Zself-profile=Zsp")
@@ -223,8 +321,37 @@ fn main() {
                 run_with_determinism_env(cmd);
             }
 
+            "PerfStatRecord" => {
+                let mut cmd = Command::new(tool_binary("PERF_BIN", "perf"));
+                let has_perf = cmd.output().is_ok();
+                assert!(has_perf);
+                // `perf stat record` counts the same events as plain `perf
+                // stat` but additionally writes a `perf.data` usable with
+                // `perf report`. The sampling adds a little overhead, so the
+                // counts are for investigation, not for stored measurements.
+                cmd.arg("stat")
+                    .env("LC_NUMERIC", "C")
+                    .arg("record")
+                    .arg("-x;")
+                    .arg("-e")
+                    .arg(perf_stat_events())
+                    .arg("--log-fd")
+                    .arg("1")
+                    .arg("setarch")
+                    .arg(std::env::consts::ARCH)
+                    .arg("-R")
+                    .arg(&tool)
+                    .args(&args);
+
+                let start = Instant::now();
+                run_with_determinism_env(cmd);
+                let dur = start.elapsed();
+                print_memory();
+                print_time(dur);
+            }
+
             "PerfRecord" => {
-                let mut cmd = Command::new("perf");
+                let mut cmd = Command::new(tool_binary("PERF_BIN", "perf"));
                 let has_perf = cmd.output().is_ok();
                 assert!(has_perf);
                 cmd.arg("record")
@@ -238,8 +365,25 @@ fn main() {
                 run_with_determinism_env(cmd);
             }
 
+            "PerfRecordBolt" => {
+                let mut cmd = Command::new(tool_binary("PERF_BIN", "perf"));
+                let has_perf = cmd.output().is_ok();
+                assert!(has_perf);
+                // LBR branch sampling in the configuration expected by
+                // `perf2bolt`. Requires hardware LBR support; `perf record`
+                // will fail outright on CPUs without it.
+                cmd.arg("record")
+                    .arg("--output=perf")
+                    .arg("--event=cycles:u")
+                    .arg("--branch-filter=any,u")
+                    .arg(&tool)
+                    .args(&args);
+
+                run_with_determinism_env(cmd);
+            }
+
             "Oprofile" => {
-                let mut cmd = Command::new("operf");
+                let mut cmd = Command::new(tool_binary("OPERF_BIN", "operf"));
                 let has_oprofile = cmd.output().is_ok();
                 assert!(has_oprofile);
                 // Other possibly useful args: --callgraph, --separate-thread
@@ -261,8 +405,30 @@ fn main() {
                 run_with_determinism_env(cmd);
             }
 
+            "Instruments" => {
+                // Apple's command-line front end to Instruments. Produces an
+                // `instruments.trace` bundle in the current directory, which
+                // can be opened in Instruments for inspection.
+                if !cfg!(target_os = "macos") {
+                    panic!("the Instruments profiler requires macOS (it runs `xcrun xctrace`)");
+                }
+                let mut cmd = Command::new("xcrun");
+                cmd.arg("xctrace")
+                    .arg("record")
+                    .arg("--template")
+                    .arg("Time Profiler")
+                    .arg("--output")
+                    .arg("instruments.trace")
+                    .arg("--launch")
+                    .arg("--")
+                    .arg(&tool)
+                    .args(&args);
+
+                run_with_determinism_env(cmd);
+            }
+
             "Cachegrind" => {
-                let mut cmd = Command::new("valgrind");
+                let mut cmd = Command::new(tool_binary("VALGRIND_BIN", "valgrind"));
                 let has_valgrind = cmd.output().is_ok();
                 assert!(has_valgrind);
 
@@ -286,7 +452,7 @@ fn main() {
             }
 
             "Callgrind" => {
-                let mut cmd = Command::new("valgrind");
+                let mut cmd = Command::new(tool_binary("VALGRIND_BIN", "valgrind"));
                 let has_valgrind = cmd.output().is_ok();
                 assert!(has_valgrind);
 
@@ -302,8 +468,29 @@ fn main() {
                 run_with_determinism_env(cmd);
             }
 
+            "CallgrindGraph" => {
+                let mut cmd = Command::new(tool_binary("VALGRIND_BIN", "valgrind"));
+                let has_valgrind = cmd.output().is_ok();
+                assert!(has_valgrind);
+
+                // Unlike plain Callgrind, also record per-instruction costs
+                // and jumps so the output file carries a call graph that
+                // KCachegrind can display. This roughly doubles the runtime
+                // on top of Callgrind's own slowdown.
+                cmd.arg("--tool=callgrind")
+                    .arg("--cache-sim=no")
+                    .arg("--branch-sim=no")
+                    .arg("--dump-instr=yes")
+                    .arg("--collect-jumps=yes")
+                    .arg("--callgrind-out-file=clgout")
+                    .arg(&tool)
+                    .args(&args);
+
+                run_with_determinism_env(cmd);
+            }
+
             "Dhat" => {
-                let mut cmd = Command::new("valgrind");
+                let mut cmd = Command::new(tool_binary("VALGRIND_BIN", "valgrind"));
                 let has_valgrind = cmd.output().is_ok();
                 assert!(has_valgrind);
                 cmd.arg("--tool=dhat")
@@ -316,7 +503,7 @@ fn main() {
             }
 
             "DhatCopy" => {
-                let mut cmd = Command::new("valgrind");
+                let mut cmd = Command::new(tool_binary("VALGRIND_BIN", "valgrind"));
                 let has_valgrind = cmd.output().is_ok();
                 assert!(has_valgrind);
                 cmd.arg("--tool=dhat")
@@ -330,7 +517,7 @@ fn main() {
             }
 
             "Massif" => {
-                let mut cmd = Command::new("valgrind");
+                let mut cmd = Command::new(tool_binary("VALGRIND_BIN", "valgrind"));
                 let has_valgrind = cmd.output().is_ok();
                 assert!(has_valgrind);
                 cmd.arg("--tool=massif")
@@ -345,6 +532,21 @@ fn main() {
                 run_with_determinism_env(cmd);
             }
 
+            "ValgrindRaw" => {
+                let mut cmd = Command::new(tool_binary("VALGRIND_BIN", "valgrind"));
+                let has_valgrind = cmd.output().is_ok();
+                assert!(has_valgrind);
+                // The flags come verbatim from the user; they are responsible
+                // for arranging output capture (e.g. an `--*-out-file` flag
+                // pointing at `valgrind-out`).
+                let valgrind_args = env::var("VALGRIND_ARGS").unwrap_or_default();
+                cmd.args(valgrind_args.split_whitespace())
+                    .arg(&tool)
+                    .args(&args);
+
+                run_with_determinism_env(cmd);
+            }
+
             "Bytehound" => {
                 let mut cmd = Command::new(tool);
                 cmd.args(args);
@@ -409,6 +611,15 @@ fn main() {
                 run_with_determinism_env(cmd);
             }
 
+            "ArtifactSize" => {
+                // A completely normal build; the artifact sizes are measured
+                // afterwards by walking the target directory.
+                let mut cmd = Command::new(tool);
+                cmd.args(&args);
+
+                run_with_determinism_env(cmd);
+            }
+
             _ => {
                 panic!("unknown wrapper: {}", wrapper);
             }
@@ -436,12 +647,31 @@ fn main() {
     }
 }
 
+/// Resolves the binary to use for an external tool. The env var (e.g.
+/// `PERF_BIN`) overrides the bare name on PATH, mirroring how `XPERF` and
+/// `TRACELOG` already work on Windows. Useful when the tool is installed
+/// under a versioned name like `perf_5.10`.
+fn tool_binary(env_var: &str, default: &str) -> String {
+    env::var(env_var).unwrap_or_else(|_| default.to_string())
+}
+
 fn process_self_profile_output(prof_out_dir: PathBuf, args: &[OsString]) {
+    // If the crate name cannot be determined, skip emitting the
+    // `!self-profile-*` lines rather than panicking inside the wrapper:
+    // aborting here would fail the whole cargo build with a confusing
+    // status, while the measure processor already treats a missing profile
+    // as "no self-profile data" and degrades gracefully.
     let crate_name = args
         .windows(2)
         .find(|args| args[0] == "--crate-name")
-        .and_then(|args| args[1].to_str())
-        .expect("rustc to be invoked with crate name");
+        .and_then(|args| args[1].to_str());
+    let Some(crate_name) = crate_name else {
+        eprintln!(
+            "rustc-fake: could not determine crate name; \
+             self-profile data dropped for this crate"
+        );
+        return;
+    };
     println!("!self-profile-dir:{}", prof_out_dir.to_str().unwrap());
     println!("!self-profile-crate:{}", crate_name);
 }
@@ -471,8 +701,16 @@ fn print_memory() {
         let mut usage = mem::zeroed();
         let r = libc::getrusage(libc::RUSAGE_CHILDREN, &mut usage);
         if r == 0 {
+            // `ru_maxrss` is in kilobytes on Linux, but in bytes on macOS;
+            // normalize to kilobytes so `max-rss` has the same unit on every
+            // OS.
+            let max_rss = if cfg!(target_os = "macos") {
+                usage.ru_maxrss / 1024
+            } else {
+                usage.ru_maxrss
+            };
             // for explanation of all the semicolons, see `print_time` below
-            println!("{};;max-rss;3;100.00", usage.ru_maxrss);
+            println!("{};;max-rss;3;100.00", max_rss);
         }
     }
 }
@@ -492,4 +730,52 @@ fn print_time(dur: Duration) {
 }
 
 #[cfg(windows)]
-fn print_memory() {}
+static CHILD_PEAK_WORKING_SET_KB: std::sync::atomic::AtomicU64 =
+    std::sync::atomic::AtomicU64::new(0);
+
+/// Queries the peak working set of the just-exited child process and stashes
+/// it for `print_memory`. Unlike `getrusage(RUSAGE_CHILDREN)` on unix this
+/// only covers the direct child, not grandchildren.
+#[cfg(windows)]
+fn record_child_peak_memory(child: &std::process::Child) {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::System::ProcessStatus::{
+        K32GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+    };
+
+    unsafe {
+        let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+        counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        let r = K32GetProcessMemoryInfo(child.as_raw_handle() as isize, &mut counters, counters.cb);
+        if r != 0 {
+            // `PeakWorkingSetSize` is in bytes; normalize to kilobytes to
+            // match the unit used on unix.
+            CHILD_PEAK_WORKING_SET_KB.store(
+                counters.PeakWorkingSetSize as u64 / 1024,
+                std::sync::atomic::Ordering::SeqCst,
+            );
+        }
+    }
+}
+
+#[cfg(windows)]
+fn print_memory() {
+    let max_rss = CHILD_PEAK_WORKING_SET_KB.load(std::sync::atomic::Ordering::SeqCst);
+    if max_rss > 0 {
+        // for explanation of all the semicolons, see `print_time` above
+        println!("{};;max-rss;3;100.00", max_rss);
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::parse_cpuset;
+
+    #[test]
+    fn parses_cpuset_lists() {
+        assert_eq!(parse_cpuset("2-3"), Some(vec![2, 3]));
+        assert_eq!(parse_cpuset("0,2,4-6"), Some(vec![0, 2, 4, 5, 6]));
+        assert_eq!(parse_cpuset("3-1"), None);
+        assert_eq!(parse_cpuset(""), None);
+    }
+}
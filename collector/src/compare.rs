@@ -38,11 +38,15 @@ static ALL_METRICS: &[Metric] = &[
 ];
 
 /// Compare 2 artifacts and print the result.
+///
+/// `threshold` limits the per-benchmark table to changes of at least that
+/// many percent; the aggregate summary always covers all results.
 pub async fn compare_artifacts(
     mut conn: Box<dyn Connection>,
     metric: Option<Metric>,
     base: Option<String>,
     modified: Option<String>,
+    threshold: f64,
 ) -> anyhow::Result<()> {
     let index = database::Index::load(&mut *conn).await;
 
@@ -105,14 +109,70 @@ pub async fn compare_artifacts(
         .await
         .unwrap();
 
-    let tuple_pstats = resp
+    let case_pstats = resp
         .into_iter()
         .map(|resp| {
             let points = resp.series.points.collect::<Vec<_>>();
-            (points[0], points[1])
+            (resp.test_case, points[0], points[1])
         })
         .collect::<Vec<_>>();
 
+    // Per-test-case report, worst regression first. Results present in only
+    // one of the two artifacts are listed at the end, without a delta.
+    #[derive(Tabled)]
+    struct TestCaseChange {
+        benchmark: String,
+        profile: String,
+        scenario: String,
+        backend: String,
+        base: String,
+        modified: String,
+        #[tabled(rename = "change")]
+        change_str: String,
+    }
+
+    let mut changes = Vec::new();
+    for (case, base_value, modified_value) in &case_pstats {
+        let change = match (base_value, modified_value) {
+            (Some(base), Some(modified)) if *base != 0.0 => Some((modified - base) / base * 100.0),
+            _ => None,
+        };
+        if matches!(change, Some(change) if change.abs() < threshold) {
+            continue;
+        }
+        changes.push((
+            change,
+            TestCaseChange {
+                benchmark: case.benchmark.to_string(),
+                profile: case.profile.to_string(),
+                scenario: case.scenario.to_string(),
+                backend: case.backend.to_string(),
+                base: base_value.map_or_else(|| "-".to_string(), |v| format!("{v:.0}")),
+                modified: modified_value.map_or_else(|| "-".to_string(), |v| format!("{v:.0}")),
+                change_str: change.map_or_else(|| "-".to_string(), |c| format!("{c:+.2}%")),
+            },
+        ));
+    }
+    changes.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) => b.total_cmp(a),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    if changes.is_empty() {
+        println!("No changes of at least {threshold}%");
+    } else {
+        println!(
+            "{}",
+            Table::new(changes.into_iter().map(|(_, change)| change))
+        );
+    }
+
+    let tuple_pstats = case_pstats
+        .iter()
+        .map(|(_, base, modified)| (*base, *modified))
+        .collect::<Vec<_>>();
+
     #[derive(Tabled)]
     struct Regression {
         count: usize,
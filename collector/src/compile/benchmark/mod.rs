@@ -25,6 +25,37 @@ fn default_runs() -> usize {
     3
 }
 
+/// When set, timing/preparation directories are created under deterministic
+/// paths instead of randomly named temp dirs, so that paths baked into
+/// profiler output (e.g. cachegrind annotations) are comparable between runs.
+static DETERMINISTIC_TEMP_DIRS: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+pub fn use_deterministic_temp_dirs() {
+    DETERMINISTIC_TEMP_DIRS.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn deterministic_temp_dirs() -> bool {
+    DETERMINISTIC_TEMP_DIRS.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+/// When set, `IncrUnchanged`-only runs populate the incremental cache with a
+/// single (discarded) `IncrFull` build and then repeat `IncrUnchanged` in the
+/// same directory, instead of paying for a fresh `IncrFull` every iteration.
+/// Only applies when `IncrUnchanged` is the sole selected scenario; any other
+/// combination (in particular `IncrPatched`) keeps the
+/// fresh-directory-per-iteration strategy and thus its usual cache state.
+static REUSE_INCREMENTAL_CACHE: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+pub fn reuse_incremental_cache() {
+    REUSE_INCREMENTAL_CACHE.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn should_reuse_incremental_cache() -> bool {
+    REUSE_INCREMENTAL_CACHE.load(std::sync::atomic::Ordering::SeqCst)
+}
+
 #[derive(
     Debug, Default, PartialEq, Copy, Clone, serde::Serialize, serde::Deserialize, clap::ValueEnum,
 )]
@@ -51,11 +82,29 @@ pub struct BenchmarkConfig {
     cargo_opts: Option<String>,
     cargo_rustc_opts: Option<String>,
     cargo_toml: Option<String>,
+    /// Workspace member to measure, passed to `cargo pkgid` to disambiguate
+    /// it (a bare probe errors with "ambiguous" in multi-member workspaces)
+    /// and used for the `-p` restriction of the final build.
+    #[serde(default)]
+    package: Option<String>,
+    /// Measure every workspace member rather than only the leaf crate: the
+    /// final build uses a workspace-wide cargo subcommand and the per-crate
+    /// stats emitted by rustc-fake are summed. Defaults to false.
+    #[serde(default)]
+    whole_workspace: bool,
     #[serde(default)]
     disabled: bool,
     #[serde(default = "default_runs")]
     runs: usize,
 
+    /// A shell command run (via `bash -c`) once in each preparation temp dir
+    /// before cargo does, for benchmarks that need a fixture generated or
+    /// fetched outside the Cargo build. It runs per prep dir -- not per
+    /// iteration -- so it must leave the sources in a state every iteration
+    /// can reuse. A non-zero exit fails the benchmark.
+    #[serde(default)]
+    setup: Option<String>,
+
     /// The file that should be touched to ensure cargo re-checks the leaf crate
     /// we're interested in. Likely, something similar to `src/lib.rs`. The
     /// default if this is not present is to touch all .rs files in the
@@ -75,6 +124,13 @@ pub struct BenchmarkConfig {
     #[serde(default)]
     excluded_scenarios: HashSet<Scenario>,
 
+    /// An explicit, ordered list of patch names to use for incremental
+    /// scenarios. When present, only the named patches are applied, in the
+    /// given order; patch files not listed here are ignored. When absent, all
+    /// `.patch` files are used, ordered by their numeric filename prefix.
+    #[serde(default)]
+    patches: Option<Vec<String>>,
+
     artifact: ArtifactType,
 }
 
@@ -92,6 +148,58 @@ impl BenchmarkConfig {
     }
 }
 
+/// A classified benchmark failure, recorded (JSON-serialized) into the
+/// database instead of a bare debug-formatted string, so that failure
+/// dashboards can distinguish e.g. hung builds from disabled benchmarks.
+/// [`BenchmarkError::from_stored`] still accepts the plain strings stored by
+/// older collectors.
+#[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BenchmarkError {
+    #[error("benchmark is disabled")]
+    Disabled,
+    #[error("build failed: {stderr}")]
+    BuildFailed { stderr: String },
+    #[error("benchmark timed out")]
+    Timeout,
+    #[error("measurement failed: {message}")]
+    MeasurementFailed { message: String },
+    #[error("{message}")]
+    Other { message: String },
+}
+
+impl BenchmarkError {
+    /// Classifies an error produced while benchmarking. The classification is
+    /// necessarily heuristic for errors that only exist as formatted text.
+    pub fn classify(error: &anyhow::Error) -> Self {
+        if error.downcast_ref::<crate::CommandTimedOut>().is_some() {
+            return BenchmarkError::Timeout;
+        }
+        let message = format!("{error:?}");
+        if message.contains("disabled benchmark") {
+            BenchmarkError::Disabled
+        } else if message.contains("Benchmark timeouted in") {
+            BenchmarkError::Timeout
+        } else if message.contains("expected success") {
+            BenchmarkError::BuildFailed { stderr: message }
+        } else if message.contains("failed to deserialize stats")
+            || message.contains("failed to collect statistics")
+        {
+            BenchmarkError::MeasurementFailed { message }
+        } else {
+            BenchmarkError::Other { message }
+        }
+    }
+
+    /// Decodes an error recorded in the database, accepting both the current
+    /// JSON encoding and the bare strings stored by older collectors.
+    pub fn from_stored(stored: &str) -> Self {
+        serde_json::from_str(stored).unwrap_or_else(|_| BenchmarkError::Other {
+            message: stored.to_string(),
+        })
+    }
+}
+
 #[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash)]
 pub struct BenchmarkName(pub String);
 
@@ -106,6 +214,14 @@ pub struct Benchmark {
     pub path: PathBuf,
     pub patches: Vec<Patch>,
     config: BenchmarkConfig,
+    /// Keeps the extraction directory of a `.tar.zst` benchmark alive for as
+    /// long as the benchmark is in use; the sources are deleted when it is
+    /// dropped.
+    _archive_dir: Option<TempDir>,
+    /// Sequence number of the next temp dir handed out for this benchmark;
+    /// only used for deterministic temp dir naming. Directories are requested
+    /// in a fixed order, so equal runs get equal paths.
+    temp_dir_index: std::sync::atomic::AtomicUsize,
 }
 
 impl Benchmark {
@@ -135,14 +251,67 @@ impl Benchmark {
             bail!("missing a perf-config.json file for `{}`", name);
         };
 
+        // Catch a missing manifest at registration time: without this, a
+        // benchmark dir lacking its Cargo.toml only fails mid-run with an
+        // opaque cargo error when the package id is first queried.
+        let manifest = path.join(config.cargo_toml.as_deref().unwrap_or("Cargo.toml"));
+        if !manifest.is_file() {
+            bail!(
+                "benchmark `{}` has no manifest at {:?}; add a Cargo.toml or point \
+                 `cargo_toml` in its perf-config.json at one",
+                name,
+                manifest
+            );
+        }
+
+        if let Some(named) = &config.patches {
+            patches = named
+                .iter()
+                .map(|patch_name| {
+                    patches
+                        .iter()
+                        .find(|patch| patch.name.as_str() == patch_name)
+                        .cloned()
+                        .ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "patch `{}` listed in perf-config.json of `{}` does not exist",
+                                patch_name,
+                                name
+                            )
+                        })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+        }
+
         Ok(Benchmark {
             name: BenchmarkName(name),
             path,
             patches,
             config,
+            _archive_dir: None,
+            temp_dir_index: std::sync::atomic::AtomicUsize::new(0),
         })
     }
 
+    /// Creates a benchmark from a `name.tar.zst` archive by extracting it
+    /// into a temporary directory, which is cleaned up when the benchmark is
+    /// dropped. The archive must contain the same layout a benchmark
+    /// directory would (i.e. `perf-config.json` etc. at the top level).
+    pub fn from_archive(name: String, archive: &Path) -> anyhow::Result<Self> {
+        let dir = TempDir::new()?;
+        let file = File::open(archive)
+            .with_context(|| format!("failed to open benchmark archive {:?}", archive))?;
+        let decoder = zstd::stream::read::Decoder::new(file)
+            .with_context(|| format!("failed to read benchmark archive {:?}", archive))?;
+        tar::Archive::new(decoder)
+            .unpack(dir.path())
+            .with_context(|| format!("failed to extract benchmark archive {:?}", archive))?;
+
+        let mut benchmark = Self::new(name, dir.path().to_path_buf())?;
+        benchmark._archive_dir = Some(dir);
+        Ok(benchmark)
+    }
+
     pub fn category(&self) -> Category {
         self.config.category
     }
@@ -168,7 +337,25 @@ impl Benchmark {
         // `tmp_dir`, rather than `base` itself.
         let mut base_dot = base.to_path_buf();
         base_dot.push(".");
-        let tmp_dir = TempDir::new()?;
+        let tmp_dir = if deterministic_temp_dirs() {
+            let index = self
+                .temp_dir_index
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let name = format!("rustc-perf-{}-{}", self.name, index);
+            // Remove any leftover dir from a previous run; reusing it would
+            // contaminate the measurement with stale caches.
+            let path = std::env::temp_dir().join(&name);
+            if path.exists() {
+                std::fs::remove_dir_all(&path)
+                    .with_context(|| format!("removing stale temp dir {}", path.display()))?;
+            }
+            tempfile::Builder::new()
+                .prefix(&name)
+                .rand_bytes(0)
+                .tempdir()?
+        } else {
+            TempDir::new()?
+        };
         Self::copy(&base_dot, tmp_dir.path())
             .with_context(|| format!("copying {} to tmp dir", self.name))?;
         Ok(tmp_dir)
@@ -209,6 +396,7 @@ impl Benchmark {
                 .cargo_toml
                 .clone()
                 .unwrap_or_else(|| String::from("Cargo.toml")),
+            package: self.config.package.clone(),
             cargo_args,
             rustc_args: self
                 .config
@@ -220,18 +408,54 @@ impl Benchmark {
                 .collect(),
             touch_file: self.config.touch_file.clone(),
             jobserver: None,
+            whole_workspace: self.config.whole_workspace,
         }
     }
 
+    /// Runs the configured `setup` command (if any) in `cwd` via `bash -c`.
+    /// The command sees the same toolchain environment the following cargo
+    /// invocations get (resolved rustc/cargo, RUSTC_BOOTSTRAP), so fixtures
+    /// are generated with the toolchain under test.
+    fn run_setup_script(&self, toolchain: &Toolchain, cwd: &Path) -> anyhow::Result<()> {
+        let Some(setup) = &self.config.setup else {
+            return Ok(());
+        };
+        let mut cmd = std::process::Command::new("bash");
+        cmd.arg("-c")
+            .arg(setup)
+            .current_dir(cwd)
+            .env("RUSTC", &toolchain.components.rustc)
+            .env("CARGO", &toolchain.components.cargo)
+            .env("CARGO_INCREMENTAL", "0")
+            .env("RUSTC_BOOTSTRAP", "1");
+        crate::command_output(&mut cmd)
+            .with_context(|| format!("setup script of benchmark `{}` failed", self.name))?;
+        Ok(())
+    }
+
     /// Run a specific benchmark under a processor + profiler combination.
+    ///
+    /// The first `warmup` timed iterations are executed (paying their full
+    /// runtime cost), but their measurements are discarded by the processor.
+    ///
+    /// `prep_parallelism` caps how many cores the (untimed) preparation phase
+    /// may use; it defaults to all available cores. Timed iterations always
+    /// run serially, since overlapping them would pollute each other's
+    /// hardware counters.
+    #[allow(clippy::too_many_arguments)]
     pub async fn measure(
         &self,
         processor: &mut dyn Processor,
         profiles: &[Profile],
         scenarios: &[Scenario],
+        // When set, restricts `IncrPatched` runs to patches with these
+        // indices; `None` runs every patch.
+        patch_indices: Option<&[usize]>,
         backends: &[CodegenBackend],
         toolchain: &Toolchain,
         iterations: Option<usize>,
+        warmup: usize,
+        prep_parallelism: Option<usize>,
     ) -> anyhow::Result<()> {
         if self.config.disabled {
             eprintln!("Skipping {}: disabled", self.name);
@@ -270,6 +494,13 @@ impl Benchmark {
             }
         }
 
+        // Run the setup command once per prep dir before cargo ever touches
+        // it; every iteration reuses the prepared sources, so the script does
+        // not run again per iteration.
+        for (_, prep_dir) in &target_dirs {
+            self.run_setup_script(toolchain, prep_dir.path())?;
+        }
+
         // In parallel (but with a limit to the number of CPUs), prepare all
         // profiles. This is done in parallel vs. sequentially because:
         //  * We don't record any measurements during this phase, so the
@@ -296,13 +527,14 @@ impl Benchmark {
         // To avoid potential problems with recompilations, artifacts compiled by
         // different codegen backends are stored in separate directories.
         let preparation_start = std::time::Instant::now();
+        let parallelism = match prep_parallelism {
+            Some(n) => n.max(1),
+            None => std::thread::available_parallelism()
+                .expect("Cannot get core count")
+                .get(),
+        };
         std::thread::scope::<_, anyhow::Result<()>>(|s| {
-            let server = jobserver::Client::new(
-                std::thread::available_parallelism()
-                    .expect("Cannot get core count")
-                    .get(),
-            )
-            .context("jobserver::new")?;
+            let server = jobserver::Client::new(parallelism).context("jobserver::new")?;
             let mut threads = Vec::with_capacity(target_dirs.len());
             for ((backend, profile), prep_dir) in &target_dirs {
                 let server = server.clone();
@@ -344,6 +576,13 @@ impl Benchmark {
 
         let benchmark_start = std::time::Instant::now();
         for ((backend, profile), prep_dir) in &target_dirs {
+            if crate::stop_requested() {
+                eprintln!(
+                    "Stop requested, skipping remaining configurations of {}",
+                    self.name
+                );
+                break;
+            }
             let backend = *backend;
             let profile = *profile;
             eprintln!(
@@ -351,11 +590,36 @@ impl Benchmark {
                 self.name, profile, scenarios, backend
             );
 
+            // With cache reuse opted in and `IncrUnchanged` as the only
+            // scenario, populate the incremental cache once with a discarded
+            // `IncrFull` build; all timed iterations then share the same
+            // directory (and cache) instead of each paying for their own
+            // `IncrFull`.
+            let mut shared_timing_dir: Option<ManuallyDrop<TempDir>> = None;
+            if should_reuse_incremental_cache()
+                && profile != Profile::Doc
+                && scenarios.iter().all(|s| *s == Scenario::IncrUnchanged)
+            {
+                let dir = ManuallyDrop::new(self.make_temp_dir(prep_dir.path())?);
+                processor.start_iteration(true);
+                self.mk_cargo_process(toolchain, dir.path(), profile, backend)
+                    .incremental(true)
+                    .processor(processor, Scenario::IncrFull, "IncrFull", None)
+                    .run_rustc(true)
+                    .await?;
+                shared_timing_dir = Some(dir);
+            }
+
             // We want at least two runs for all benchmarks (since we run
-            // self-profile separately).
-            processor.start_first_collection();
-            for i in 0..std::cmp::max(iterations, 2) {
-                if i == 1 {
+            // self-profile separately). Warmup iterations run first, before
+            // the first collection, so that the self-profile run (which
+            // happens in the first collection) is not discarded.
+            for i in 0..warmup + std::cmp::max(iterations, 2) {
+                let is_warmup = i < warmup;
+                if i == warmup {
+                    processor.start_first_collection();
+                }
+                if i == warmup + 1 {
                     let different = processor.finished_first_collection();
                     if iterations == 1 && !different {
                         // Don't run twice if this processor doesn't need it and
@@ -363,10 +627,22 @@ impl Benchmark {
                         break;
                     }
                 }
-                log::debug!("Benchmark iteration {}/{}", i + 1, iterations);
+                processor.start_iteration(is_warmup);
+                if is_warmup {
+                    log::debug!("Warmup iteration {}/{}", i + 1, warmup);
+                } else {
+                    log::debug!("Benchmark iteration {}/{}", i + 1 - warmup, iterations);
+                }
                 // Don't delete the directory on error.
-                let timing_dir = ManuallyDrop::new(self.make_temp_dir(prep_dir.path())?);
-                let cwd = timing_dir.path();
+                let timing_dir = match &shared_timing_dir {
+                    Some(_) => None,
+                    None => Some(ManuallyDrop::new(self.make_temp_dir(prep_dir.path())?)),
+                };
+                let cwd = timing_dir
+                    .as_ref()
+                    .or(shared_timing_dir.as_ref())
+                    .unwrap()
+                    .path();
 
                 // A full non-incremental build.
                 if scenarios.contains(&Scenario::Full) {
@@ -380,7 +656,9 @@ impl Benchmark {
                 if profile != Profile::Doc {
                     // An incremental  from scratch (slowest incremental case).
                     // This is required for any subsequent incremental builds.
-                    if scenarios.iter().any(|s| s.is_incr()) {
+                    // With a shared timing dir the cache was populated once
+                    // up front, so this is skipped per iteration.
+                    if scenarios.iter().any(|s| s.is_incr()) && shared_timing_dir.is_none() {
                         self.mk_cargo_process(toolchain, cwd, profile, backend)
                             .incremental(true)
                             .processor(processor, Scenario::IncrFull, "IncrFull", None)
@@ -399,6 +677,11 @@ impl Benchmark {
 
                     if scenarios.contains(&Scenario::IncrPatched) {
                         for (i, patch) in self.patches.iter().enumerate() {
+                            if let Some(indices) = patch_indices {
+                                if !indices.contains(&i) {
+                                    continue;
+                                }
+                            }
                             log::debug!("applying patch {}", patch.name);
                             patch.apply(cwd).map_err(|s| anyhow::anyhow!("{}", s))?;
 
@@ -418,8 +701,19 @@ impl Benchmark {
                         }
                     }
                 }
-                timing_dirs.push(timing_dir);
+                processor
+                    .verify_clean(cwd)
+                    .with_context(|| format!("benchmark {} left a dirty environment", self.name))?;
+                if let Some(timing_dir) = timing_dir {
+                    timing_dirs.push(timing_dir);
+                }
+            }
+            if let Some(dir) = shared_timing_dir {
+                timing_dirs.push(dir);
             }
+            // All iterations of this (backend, profile) configuration are
+            // done; let the processor treat this as a checkpoint.
+            processor.finished_configuration(backend, profile);
         }
         log::trace!(
             "benchmarking {} took {:.3} seconds",
@@ -443,6 +737,41 @@ pub fn compile_benchmark_dir() -> PathBuf {
     PathBuf::from("collector/compile-benchmarks")
 }
 
+/// Optional file at the root of the benchmark directory listing benchmarks
+/// that should not be registered at all, one name per line, optionally
+/// followed by whitespace and a reason. `#` starts a comment. Unlike the
+/// per-benchmark `disabled` flag in perf-config.json (which records an error
+/// for the benchmark), entries here make the collector behave as if the
+/// benchmark did not exist, and operators can edit the list without
+/// recompiling.
+const DISABLED_BENCHMARKS_FILE: &str = "disabled-benchmarks.txt";
+
+/// Maps each benchmark named in [`DISABLED_BENCHMARKS_FILE`] to its (possibly
+/// empty) reason. A missing file means nothing is disabled.
+fn load_disabled_benchmarks(benchmark_dir: &Path) -> anyhow::Result<HashMap<String, String>> {
+    let path = benchmark_dir.join(DISABLED_BENCHMARKS_FILE);
+    let mut disabled = HashMap::new();
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(disabled),
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read `{}`", path.display()));
+        }
+    };
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (name, reason) = match line.split_once(char::is_whitespace) {
+            Some((name, reason)) => (name, reason.trim()),
+            None => (line, ""),
+        };
+        disabled.insert(name.to_string(), reason.to_string());
+    }
+    Ok(disabled)
+}
+
 pub fn get_compile_benchmarks(
     benchmark_dir: &Path,
     include: &[String],
@@ -450,6 +779,7 @@ pub fn get_compile_benchmarks(
     exclude_suffix: &[String],
 ) -> anyhow::Result<Vec<Benchmark>> {
     let mut benchmarks = Vec::new();
+    let disabled = load_disabled_benchmarks(benchmark_dir)?;
 
     let mut paths = Vec::new();
     for entry in std::fs::read_dir(benchmark_dir)
@@ -462,12 +792,15 @@ pub fn get_compile_benchmarks(
             Err(e) => bail!("non-utf8 benchmark name: {:?}", e),
         };
 
-        if !entry.file_type()?.is_dir() {
+        if entry.file_type()?.is_dir() {
+            paths.push((path, name));
+        } else if let Some(name) = name.strip_suffix(".tar.zst") {
+            // Large benchmarks may be checked in as compressed archives
+            // rather than directory trees; they are extracted on demand.
+            paths.push((path, name.to_string()));
+        } else {
             debug!("benchmark {} - ignored", name);
-            continue;
         }
-
-        paths.push((path, name));
     }
 
     // For each --include/--exclude entry, we count how many times it's used,
@@ -481,6 +814,21 @@ pub fn get_compile_benchmarks(
     let mut exclude_suffixes = to_hashmap(exclude_suffix);
 
     for (path, name) in paths.clone() {
+        if let Some(reason) = disabled.get(&name) {
+            if reason.is_empty() {
+                eprintln!(
+                    "Skipping {}: disabled in {}",
+                    name, DISABLED_BENCHMARKS_FILE
+                );
+            } else {
+                eprintln!(
+                    "Skipping {}: disabled in {} ({})",
+                    name, DISABLED_BENCHMARKS_FILE, reason
+                );
+            }
+            continue;
+        }
+
         let mut skip = false;
 
         let name_matches_prefix = |prefixes: &mut HashMap<&str, usize>| {
@@ -501,7 +849,23 @@ pub fn get_compile_benchmarks(
         }
 
         debug!("benchmark `{}`- registered", name);
-        benchmarks.push(Benchmark::new(name, path)?);
+        let benchmark = if path.is_dir() {
+            Benchmark::new(name, path)?
+        } else {
+            Benchmark::from_archive(name, &path)?
+        };
+        benchmarks.push(benchmark);
+    }
+
+    // Stale entries (e.g. for a benchmark that has since been removed) only
+    // warn: a leftover line in the file should not abort collection.
+    for name in disabled.keys() {
+        if !paths.iter().any(|(_, n)| n == name) {
+            eprintln!(
+                "Warning: {} lists unknown benchmark `{}`",
+                DISABLED_BENCHMARKS_FILE, name
+            );
+        }
     }
 
     // All prefixes/suffixes must be used at least once. This is to catch typos.
@@ -537,6 +901,27 @@ Expected zero or more entries or substrings from list: {:?}."#,
     Ok(benchmarks)
 }
 
+/// Reorders `items` with a Fisher-Yates shuffle driven by a seeded xorshift
+/// RNG, so a given seed always produces the same order. Used to decorrelate
+/// benchmark identity from machine thermal state across runs: always
+/// benchmarking in sorted order means the same benchmarks run while the
+/// machine is cold.
+pub fn shuffle_benchmarks<T>(items: &mut [T], seed: u64) {
+    // Xorshift gets stuck at zero; substitute a fixed non-zero state for
+    // seed 0 rather than forcing a bit, which would alias adjacent seeds.
+    let mut state = if seed == 0 { 0x9e3779b97f4a7c15 } else { seed };
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
 /// Helper to verify if a benchmark name matches a given substring, like a prefix or a suffix. The
 /// `predicate` closure will be passed each substring from `substrings` until it returns true, and
 /// in that case the substring's number of uses in the map will be increased.
@@ -555,7 +940,7 @@ fn substring_matches(
 
 #[cfg(test)]
 mod tests {
-    use crate::compile::benchmark::get_compile_benchmarks;
+    use crate::compile::benchmark::{get_compile_benchmarks, shuffle_benchmarks};
     use std::path::Path;
 
     #[test]
@@ -568,4 +953,101 @@ mod tests {
                 .unwrap();
         assert!(!benchmarks.is_empty());
     }
+
+    #[test]
+    fn package_selection_is_parsed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("perf-config.json"),
+            r#"{"category": "primary", "artifact": "library", "package": "member-a"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[workspace]\n").unwrap();
+
+        let benchmark =
+            super::Benchmark::new("workspace-bench".to_string(), dir.path().to_path_buf()).unwrap();
+        assert_eq!(benchmark.config.package.as_deref(), Some("member-a"));
+    }
+
+    #[test]
+    fn setup_script_runs_in_prep_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("perf-config.json"),
+            r#"{"category": "primary", "artifact": "library", "setup": "touch generated.rs"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let benchmark =
+            super::Benchmark::new("with-setup".to_string(), dir.path().to_path_buf()).unwrap();
+        let toolchain = crate::toolchain::Toolchain {
+            components: Default::default(),
+            id: "test".to_string(),
+            triple: String::new(),
+        };
+        benchmark.run_setup_script(&toolchain, dir.path()).unwrap();
+        assert!(dir.path().join("generated.rs").is_file());
+    }
+
+    #[test]
+    fn missing_manifest_is_rejected_at_registration() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("perf-config.json"),
+            r#"{"category": "primary", "artifact": "binary"}"#,
+        )
+        .unwrap();
+
+        let error = super::Benchmark::new("no-manifest".to_string(), dir.path().to_path_buf())
+            .err()
+            .expect("benchmark without a Cargo.toml should be rejected")
+            .to_string();
+        assert!(error.contains("no-manifest"), "{error}");
+        assert!(error.contains("no manifest"), "{error}");
+    }
+
+    #[test]
+    fn disabled_benchmarks_file_is_parsed_and_honored() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in ["keep-me", "skip-me"] {
+            let bench_dir = dir.path().join(name);
+            std::fs::create_dir(&bench_dir).unwrap();
+            std::fs::write(
+                bench_dir.join("perf-config.json"),
+                r#"{"category": "primary", "artifact": "library"}"#,
+            )
+            .unwrap();
+            std::fs::write(bench_dir.join("Cargo.toml"), "[package]\n").unwrap();
+        }
+        std::fs::write(
+            dir.path().join(super::DISABLED_BENCHMARKS_FILE),
+            "# comment line\n\nskip-me   broken since rustc 1.70 # trailing comment\n",
+        )
+        .unwrap();
+
+        let disabled = super::load_disabled_benchmarks(dir.path()).unwrap();
+        assert_eq!(
+            disabled.get("skip-me").map(|s| s.as_str()),
+            Some("broken since rustc 1.70")
+        );
+        assert_eq!(disabled.len(), 1);
+
+        let benchmarks = get_compile_benchmarks(dir.path(), &[], &[], &[]).unwrap();
+        let names: Vec<_> = benchmarks.iter().map(|b| b.name.0.as_str()).collect();
+        assert_eq!(names, vec!["keep-me"]);
+    }
+
+    #[test]
+    fn shuffle_is_reproducible() {
+        let mut first: Vec<u32> = (0..20).collect();
+        let mut second = first.clone();
+        shuffle_benchmarks(&mut first, 42);
+        shuffle_benchmarks(&mut second, 42);
+        assert_eq!(first, second);
+
+        let mut other = (0..20).collect::<Vec<u32>>();
+        shuffle_benchmarks(&mut other, 43);
+        assert_ne!(first, other);
+    }
 }
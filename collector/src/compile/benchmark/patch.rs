@@ -13,7 +13,7 @@ pub struct Patch {
 
 impl PartialEq for Patch {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name
+        self.normalized_name() == other.normalized_name()
     }
 }
 
@@ -21,7 +21,7 @@ impl Eq for Patch {}
 
 impl hash::Hash for Patch {
     fn hash<H: hash::Hasher>(&self, h: &mut H) {
-        self.name.hash(h);
+        self.normalized_name().hash(h);
     }
 }
 
@@ -57,14 +57,71 @@ impl Patch {
         }
     }
 
+    /// The patch name normalized for identity comparisons: trimmed and
+    /// lowercased, so renaming e.g. `0-PrintLn.patch` to `0-println.patch`
+    /// does not create a new patch identity in historical data.
+    pub fn normalized_name(&self) -> String {
+        self.name.trim().to_lowercase()
+    }
+
     pub fn apply(&self, dir: &Path) -> anyhow::Result<()> {
         log::debug!("applying {} to {:?}", self.name, dir);
 
+        // `git apply` understands git-format patches with renames and binary
+        // hunks, so try it first.
         let mut cmd = Command::new("git");
-        cmd.current_dir(dir).args(["apply"]).arg(&*self.path);
+        cmd.current_dir(dir).args(["apply", "-p1"]).arg(&*self.path);
+        let git_error = match command_output(&mut cmd) {
+            Ok(_) => return Ok(()),
+            Err(error) => error,
+        };
+
+        // Classic patches may still apply with plain `patch` even when
+        // `git apply` rejects them, or when git is not available at all.
+        let mut cmd = Command::new("patch");
+        cmd.current_dir(dir).args(["-Np1", "-i"]).arg(&*self.path);
+        match command_output(&mut cmd) {
+            Ok(_) => Ok(()),
+            Err(patch_error) => Err(anyhow::anyhow!(
+                "failed to apply patch {}: `git apply` failed with {:#}; `patch` failed with {:#}",
+                self.name,
+                git_error,
+                patch_error
+            )),
+        }
+    }
+}
 
-        command_output(&mut cmd)?;
+#[cfg(test)]
+mod tests {
+    use super::Patch;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::path::PathBuf;
+
+    fn patch(name: &str) -> Patch {
+        Patch {
+            index: 0,
+            name: name.into(),
+            path: PathBuf::new(),
+        }
+    }
+
+    fn hash(patch: &Patch) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        patch.hash(&mut hasher);
+        hasher.finish()
+    }
 
-        Ok(())
+    #[test]
+    fn patch_identity_ignores_case_and_whitespace() {
+        let a = patch("PrintLn");
+        let b = patch("println");
+        let c = patch(" println ");
+        assert_eq!(a, b);
+        assert_eq!(b, c);
+        assert_eq!(hash(&a), hash(&b));
+        assert_eq!(hash(&b), hash(&c));
+        assert_ne!(patch("println"), patch("typo"));
     }
 }
@@ -25,4 +25,30 @@ impl Profile {
             Profile::Clippy,
         ]
     }
+
+    /// The canonical mapping from a CLI profile to the profile stored in the
+    /// database. Keep this as the single place that performs the conversion.
+    pub fn to_database(self) -> database::Profile {
+        match self {
+            Profile::Check => database::Profile::Check,
+            Profile::Debug => database::Profile::Debug,
+            Profile::Doc => database::Profile::Doc,
+            Profile::Opt => database::Profile::Opt,
+            Profile::Clippy => database::Profile::Clippy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Profile;
+
+    #[test]
+    fn database_mapping_is_pinned() {
+        assert_eq!(Profile::Check.to_database(), database::Profile::Check);
+        assert_eq!(Profile::Debug.to_database(), database::Profile::Debug);
+        assert_eq!(Profile::Doc.to_database(), database::Profile::Doc);
+        assert_eq!(Profile::Opt.to_database(), database::Profile::Opt);
+        assert_eq!(Profile::Clippy.to_database(), database::Profile::Clippy);
+    }
 }
@@ -1,3 +1,5 @@
+use database::PatchName;
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, clap::ValueEnum, serde::Deserialize)]
 #[value(rename_all = "PascalCase")]
 pub enum Scenario {
@@ -27,4 +29,42 @@ impl Scenario {
             Scenario::IncrFull | Scenario::IncrUnchanged | Scenario::IncrPatched
         )
     }
+
+    /// The canonical mapping from a CLI scenario to the scenario stored in the
+    /// database. Keep this as the single place that performs the conversion,
+    /// so that the two sets of names cannot drift apart.
+    pub fn to_database(self, patch_name: Option<PatchName>) -> database::Scenario {
+        match self {
+            Scenario::Full => database::Scenario::Empty,
+            Scenario::IncrFull => database::Scenario::IncrementalEmpty,
+            Scenario::IncrUnchanged => database::Scenario::IncrementalFresh,
+            Scenario::IncrPatched => database::Scenario::IncrementalPatch(
+                patch_name.expect("IncrPatched scenario requires a patch name"),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scenario;
+    use database::PatchName;
+
+    #[test]
+    fn database_mapping_is_pinned() {
+        assert_eq!(Scenario::Full.to_database(None), database::Scenario::Empty);
+        assert_eq!(
+            Scenario::IncrFull.to_database(None),
+            database::Scenario::IncrementalEmpty
+        );
+        assert_eq!(
+            Scenario::IncrUnchanged.to_database(None),
+            database::Scenario::IncrementalFresh
+        );
+        let patch = PatchName::from("println");
+        assert_eq!(
+            Scenario::IncrPatched.to_database(Some(patch)),
+            database::Scenario::IncrementalPatch(patch)
+        );
+    }
 }
@@ -1,11 +1,10 @@
 use crate::compile::benchmark::codegen_backend::CodegenBackend;
 use crate::compile::benchmark::profile::Profile;
-use crate::compile::benchmark::scenario::Scenario;
 use crate::compile::benchmark::BenchmarkName;
 use crate::compile::execute;
 use crate::compile::execute::{
-    rustc, DeserializeStatError, PerfTool, ProcessOutputData, Processor, Retry, SelfProfileFiles,
-    Stats,
+    rustc, DeserializeStatError, PerfTool, ProcessOutputData, Processor, Retry, RetryBudget,
+    SelfProfileFiles, Stats,
 };
 use crate::toolchain::Toolchain;
 use crate::utils::git::get_rustc_perf_commit;
@@ -44,7 +43,8 @@ pub struct BenchProcessor<'a> {
     artifact_row_id: database::ArtifactIdNumber,
     is_first_collection: bool,
     is_self_profile: bool,
-    tries: u8,
+    is_warmup: bool,
+    retries: RetryBudget,
     self_profiles: Vec<RecordedSelfProfile>,
 }
 
@@ -55,6 +55,7 @@ impl<'a> BenchProcessor<'a> {
         artifact: &'a database::ArtifactId,
         artifact_row_id: database::ArtifactIdNumber,
         is_self_profile: bool,
+        max_retries: u8,
     ) -> Self {
         // Check we have `perf` or (`xperf.exe` and `tracelog.exe`)  available.
         if cfg!(unix) {
@@ -80,7 +81,8 @@ impl<'a> BenchProcessor<'a> {
             artifact_row_id,
             is_first_collection: true,
             is_self_profile,
-            tries: 0,
+            is_warmup: false,
+            retries: RetryBudget::new(max_retries),
             self_profiles: vec![],
         }
     }
@@ -135,6 +137,10 @@ impl Processor for BenchProcessor<'_> {
         }
     }
 
+    fn start_iteration(&mut self, warmup: bool) {
+        self.is_warmup = warmup;
+    }
+
     fn start_first_collection(&mut self) {
         self.is_first_collection = true;
     }
@@ -146,6 +152,12 @@ impl Processor for BenchProcessor<'_> {
         self.perf_tool() != original
     }
 
+    fn finished_configuration(&mut self, _backend: CodegenBackend, _profile: Profile) {
+        // The retry budget is per configuration; a flaky Check build should
+        // not eat into the retries available to the Opt build that follows.
+        self.retries.reset();
+    }
+
     fn process_output<'b>(
         &'b mut self,
         data: &'b ProcessOutputData<'_>,
@@ -154,6 +166,11 @@ impl Processor for BenchProcessor<'_> {
         Box::pin(async move {
             match execute::process_stat_output(output) {
                 Ok(mut res) => {
+                    if self.is_warmup {
+                        // Warmup iterations pay the full build cost, but their
+                        // measurements are deliberately discarded.
+                        return Ok(Retry::No);
+                    }
                     if let Some(ref profile) = res.1 {
                         execute::store_artifact_sizes_into_stats(&mut res.0, profile);
                     }
@@ -163,23 +180,18 @@ impl Processor for BenchProcessor<'_> {
                             execute::store_documentation_size_into_stats(&mut res.0, &doc_dir);
                         }
                     }
+                    // The wall time of the whole cargo invocation; analysis
+                    // can subtract rustc's `wall-time` from it to see cargo's
+                    // own scheduling/metadata overhead.
+                    res.0.insert(
+                        "cargo-wall-time".to_string(),
+                        data.cargo_wall_time.as_secs_f64(),
+                    );
 
-                    let scenario = match data.scenario {
-                        Scenario::Full => database::Scenario::Empty,
-                        Scenario::IncrFull => database::Scenario::IncrementalEmpty,
-                        Scenario::IncrUnchanged => database::Scenario::IncrementalFresh,
-                        Scenario::IncrPatched => {
-                            let patch = data.patch.unwrap();
-                            database::Scenario::IncrementalPatch(patch.name)
-                        }
-                    };
-                    let profile = match data.profile {
-                        Profile::Check => database::Profile::Check,
-                        Profile::Debug => database::Profile::Debug,
-                        Profile::Doc => database::Profile::Doc,
-                        Profile::Opt => database::Profile::Opt,
-                        Profile::Clippy => database::Profile::Clippy,
-                    };
+                    let scenario = data
+                        .scenario
+                        .to_database(data.patch.map(|patch| patch.name));
+                    let profile = data.profile.to_database();
 
                     let version = get_rustc_perf_commit();
                     let collection = self.conn.collection_id(&version).await;
@@ -196,7 +208,7 @@ impl Processor for BenchProcessor<'_> {
                         // are not realistic. Do not store the metrics that are affected by
                         // self-profiling into the DB for self-profile runs to avoid unnecessary
                         // DB storage.
-                        res.0.stats.retain(|key, _| key.starts_with("size:"));
+                        res.0.stats.retain(|key, _| key.name().starts_with("size:"));
                     }
 
                     self.insert_stats(collection, scenario, profile, data.backend, res.0)
@@ -205,16 +217,31 @@ impl Processor for BenchProcessor<'_> {
                     Ok(Retry::No)
                 }
                 Err(DeserializeStatError::NoOutput(output)) => {
-                    if self.tries < 5 {
+                    if self.retries.try_again() {
                         log::warn!(
                             "failed to deserialize stats, retrying (try {}); output: {:?}",
-                            self.tries,
+                            self.retries.tries(),
                             output
                         );
-                        self.tries += 1;
                         Ok(Retry::Yes)
                     } else {
-                        panic!("failed to collect statistics after 5 tries");
+                        anyhow::bail!(
+                            "failed to collect statistics after {} tries",
+                            self.retries.max_retries()
+                        );
+                    }
+                }
+                Err(error @ DeserializeStatError::PartialMeasurement { .. }) => {
+                    // Counter multiplexing on a contended machine; transient,
+                    // so retry within the same budget as missing output.
+                    if self.retries.try_again() {
+                        log::warn!("{error}, retrying (try {})", self.retries.tries());
+                        Ok(Retry::Yes)
+                    } else {
+                        anyhow::bail!(
+                            "failed to collect statistics after {} tries: {error}",
+                            self.retries.max_retries()
+                        );
                     }
                 }
                 Err(
@@ -271,6 +298,95 @@ impl Processor for BenchProcessor<'_> {
     }
 }
 
+/// Runs benchmarks under `perf stat` like [`BenchProcessor`], but keeps the
+/// collected [`Stats`] in memory instead of recording them into a database.
+/// Used by `profile --also-measure` to capture the headline numbers alongside
+/// a profiler artifact, using the same parsing as a normal bench run.
+#[derive(Default)]
+pub struct StatsProcessor {
+    is_warmup: bool,
+    /// Collected stats, keyed by `<profile>-<scenario>` as used in profiler
+    /// output filenames. Repeated iterations of the same configuration are
+    /// folded together with [`Stats::combine_min`].
+    results: std::collections::BTreeMap<String, Stats>,
+}
+
+/// The serialized form of [`StatsProcessor`] results. Besides the stats it
+/// records how per-iteration values were combined, so consumers of the stored
+/// file do not have to guess.
+#[derive(serde::Serialize)]
+pub struct StatsReport {
+    /// Deterministic stats (instruction counts and friends, see
+    /// [`Stats::is_deterministic`]) keep the per-stat minimum across
+    /// iterations -- the cleanest run -- while genuinely varying stats like
+    /// wall-time keep the last iteration's value.
+    pub combination_policy: &'static str,
+    pub results: std::collections::BTreeMap<String, std::collections::BTreeMap<String, f64>>,
+}
+
+impl StatsProcessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_results(self) -> StatsReport {
+        StatsReport {
+            combination_policy: "min for deterministic stats, last iteration otherwise",
+            results: self
+                .results
+                .into_iter()
+                .map(|(key, stats)| (key, stats.as_sorted_map()))
+                .collect(),
+        }
+    }
+}
+
+impl Processor for StatsProcessor {
+    fn perf_tool(&self) -> PerfTool {
+        if cfg!(unix) {
+            PerfTool::BenchTool(Bencher::PerfStat)
+        } else {
+            PerfTool::BenchTool(Bencher::XperfStat)
+        }
+    }
+
+    fn start_iteration(&mut self, warmup: bool) {
+        self.is_warmup = warmup;
+    }
+
+    fn process_output<'b>(
+        &'b mut self,
+        data: &'b ProcessOutputData<'_>,
+        output: process::Output,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Retry>> + 'b>> {
+        Box::pin(async move {
+            match execute::process_stat_output(output) {
+                Ok(mut res) => {
+                    if self.is_warmup {
+                        return Ok(Retry::No);
+                    }
+                    if let Some(ref profile) = res.1 {
+                        execute::store_artifact_sizes_into_stats(&mut res.0, profile);
+                    }
+                    let key = format!("{:?}-{}", data.profile, data.scenario_str);
+                    match self.results.entry(key) {
+                        std::collections::btree_map::Entry::Occupied(mut entry) => {
+                            entry.get_mut().combine_min(res.0);
+                        }
+                        std::collections::btree_map::Entry::Vacant(entry) => {
+                            entry.insert(res.0);
+                        }
+                    }
+                    Ok(Retry::No)
+                }
+                // There is no retry budget here: a one-shot side measurement
+                // that fails is simply reported.
+                Err(error) => anyhow::bail!("failed to collect statistics: {error}"),
+            }
+        })
+    }
+}
+
 /// Uploads self-profile results to S3
 struct SelfProfileS3Upload(
     std::process::Child,
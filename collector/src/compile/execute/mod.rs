@@ -7,11 +7,12 @@ use crate::compile::benchmark::scenario::Scenario;
 use crate::compile::benchmark::BenchmarkName;
 use crate::toolchain::Toolchain;
 use crate::utils::fs::EnsureImmutableFile;
-use crate::{async_command_output, command_output, utils};
+use crate::{async_command_output_with_timeout, command_output_retrying, utils};
 use analyzeme::ArtifactSize;
 use anyhow::Context;
 use bencher::Bencher;
-use std::collections::HashMap;
+use database::metric::Metric;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
 use std::future::Future;
@@ -20,13 +21,47 @@ use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::process::{self, Command};
 use std::str;
-use std::sync::LazyLock;
+use std::sync::{LazyLock, OnceLock};
 
 pub mod bencher;
 mod etw_parser;
 pub mod profiler;
 mod rustc;
 
+/// How long a single rustc/cargo invocation may run before it is killed and
+/// the benchmark recorded as errored. The generous default only guards
+/// against completely hung builds; it can be overridden with
+/// `--build-timeout`.
+const DEFAULT_BUILD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60 * 30);
+
+static BUILD_TIMEOUT: OnceLock<std::time::Duration> = OnceLock::new();
+
+/// Overrides the per-invocation build timeout. Must be called at most once,
+/// before any benchmark runs.
+pub fn set_build_timeout(timeout: std::time::Duration) {
+    BUILD_TIMEOUT
+        .set(timeout)
+        .expect("build timeout already configured");
+}
+
+fn build_timeout() -> std::time::Duration {
+    *BUILD_TIMEOUT.get().unwrap_or(&DEFAULT_BUILD_TIMEOUT)
+}
+
+static DUMP_COMMANDS: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Makes [`CargoProcess::run_rustc`] print each fully constructed cargo
+/// invocation (arguments plus the environment set on it) and return without
+/// executing it, so a benchmark's build can be reproduced by hand. Nothing is
+/// measured or recorded in this mode.
+pub fn dump_commands() {
+    DUMP_COMMANDS.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn should_dump_commands() -> bool {
+    DUMP_COMMANDS.load(std::sync::atomic::Ordering::SeqCst)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum PerfTool {
     BenchTool(Bencher),
@@ -53,19 +88,26 @@ impl PerfTool {
             | BenchTool(XperfStat)
             | BenchTool(XperfStatSelfProfile)
             | ProfileTool(SelfProfile)
+            | ProfileTool(SelfProfileJson)
             | ProfileTool(PerfRecord)
+            | ProfileTool(PerfStatRecord)
+            | ProfileTool(PerfRecordBolt)
             | ProfileTool(Oprofile)
             | ProfileTool(Samply)
+            | ProfileTool(Instruments)
             | ProfileTool(Cachegrind)
             | ProfileTool(Callgrind)
+            | ProfileTool(CallgrindGraph)
             | ProfileTool(Dhat)
             | ProfileTool(DhatCopy)
             | ProfileTool(Massif)
+            | ProfileTool(ValgrindRaw)
             | ProfileTool(Bytehound)
             | ProfileTool(Eprintln)
             | ProfileTool(DepGraph)
             | ProfileTool(MonoItems)
-            | ProfileTool(LlvmIr) => {
+            | ProfileTool(LlvmIr)
+            | ProfileTool(ArtifactSize) => {
                 if profile == Profile::Doc {
                     Some("rustdoc")
                 } else {
@@ -76,6 +118,8 @@ impl PerfTool {
                 Profile::Debug | Profile::Opt => Some("llvm-lines"),
                 Profile::Check | Profile::Doc | Profile::Clippy => None,
             },
+            // Never invokes rustc at all; handled specially in `run_rustc`.
+            ProfileTool(CrateGraph) => Some("tree"),
         }
     }
 
@@ -87,7 +131,9 @@ impl PerfTool {
         matches!(self, PerfTool::ProfileTool(profiler::Profiler::LlvmLines))
     }
 
-    fn is_scenario_allowed(&self, scenario: Scenario) -> bool {
+    /// Whether this tool can meaningfully run the given scenario; also used
+    /// by `list-profilers` for introspection.
+    pub fn is_scenario_allowed(&self, scenario: Scenario) -> bool {
         use bencher::Bencher::*;
         use profiler::Profiler::*;
         use PerfTool::*;
@@ -97,20 +143,31 @@ impl PerfTool {
             | BenchTool(XperfStat)
             | BenchTool(XperfStatSelfProfile)
             | ProfileTool(SelfProfile)
+            | ProfileTool(SelfProfileJson)
             | ProfileTool(PerfRecord)
+            | ProfileTool(PerfStatRecord)
             | ProfileTool(Oprofile)
             | ProfileTool(Samply)
+            | ProfileTool(Instruments)
             | ProfileTool(Cachegrind)
             | ProfileTool(Callgrind)
+            | ProfileTool(CallgrindGraph)
             | ProfileTool(Dhat)
             | ProfileTool(DhatCopy)
             | ProfileTool(Massif)
+            | ProfileTool(ValgrindRaw)
             | ProfileTool(Bytehound)
             | ProfileTool(MonoItems)
             | ProfileTool(LlvmIr)
+            | ProfileTool(ArtifactSize)
             | ProfileTool(Eprintln) => true,
             // only incremental
             ProfileTool(DepGraph) => scenario != Scenario::Full,
+            // The crate graph does not depend on the build kind, so capture
+            // it only once per benchmark.
+            ProfileTool(CrateGraph) => scenario == Scenario::Full,
+            // BOLT profiles are only meaningful for full builds.
+            ProfileTool(PerfRecordBolt) => scenario == Scenario::Full,
             ProfileTool(LlvmLines) => scenario == Scenario::Full,
         }
     }
@@ -125,10 +182,14 @@ pub struct CargoProcess<'a> {
     pub processor_etc: Option<(&'a mut dyn Processor, Scenario, &'a str, Option<&'a Patch>)>,
     pub processor_name: BenchmarkName,
     pub manifest_path: String,
+    /// Disambiguates `cargo pkgid` (and thus the `-p` restriction) for
+    /// workspace-shaped benchmarks, where a bare `cargo pkgid` is ambiguous.
+    pub package: Option<String>,
     pub cargo_args: Vec<String>,
     pub rustc_args: Vec<String>,
     pub touch_file: Option<String>,
     pub jobserver: Option<jobserver::Client>,
+    pub whole_workspace: bool,
 }
 /// Returns an optional list of Performance CPU cores, if the system has P and E cores.
 /// This list *should* be in a format suitable for the `taskset` command.
@@ -255,10 +316,20 @@ impl<'a> CargoProcess<'a> {
     }
 
     fn get_pkgid(&self, cwd: &Path) -> anyhow::Result<String> {
-        let mut pkgid_cmd = self.base_command(cwd, "pkgid");
-        let out = command_output(&mut pkgid_cmd)
-            .with_context(|| format!("failed to obtain pkgid in '{:?}'", cwd))?
-            .stdout;
+        // `cargo pkgid` occasionally fails transiently (it may need to touch
+        // the registry), so allow a couple of retries.
+        let out = command_output_retrying(
+            || {
+                let mut pkgid_cmd = self.base_command(cwd, "pkgid");
+                if let Some(package) = &self.package {
+                    pkgid_cmd.arg(package);
+                }
+                pkgid_cmd
+            },
+            3,
+        )
+        .with_context(|| format!("failed to obtain pkgid in '{:?}'", cwd))?
+        .stdout;
         let package_id = str::from_utf8(&out).unwrap();
         Ok(package_id.trim().to_string())
     }
@@ -282,6 +353,58 @@ impl<'a> CargoProcess<'a> {
             if needs_final { "benchmark" } else { "dependencies" }
         );
 
+        // The crate graph capture runs `cargo tree`, which does not involve
+        // rustc at all, so it bypasses the whole rustc-wrapping machinery
+        // below.
+        let is_crate_graph = self
+            .processor_etc
+            .as_ref()
+            .map(|v| v.0.perf_tool() == PerfTool::ProfileTool(profiler::Profiler::CrateGraph))
+            .unwrap_or(false);
+        if is_crate_graph {
+            if !needs_final {
+                // Nothing to prepare: `cargo tree` does not build anything.
+                return Ok(());
+            }
+            let scenario = self.processor_etc.as_ref().map(|v| v.1).unwrap();
+            if !PerfTool::ProfileTool(profiler::Profiler::CrateGraph).is_scenario_allowed(scenario)
+            {
+                return Err(anyhow::anyhow!(
+                    "this perf tool doesn't support {:?} scenarios",
+                    scenario
+                ));
+            }
+
+            let mut cmd = self.base_command(self.cwd, "tree");
+            cmd.arg("--prefix").arg("none");
+            log::debug!("{:?}", cmd);
+
+            if should_dump_commands() {
+                println!("cd {} && {:?}", self.cwd.display(), cmd);
+                return Ok(());
+            }
+
+            let cmd = tokio::process::Command::from(cmd);
+            let start = std::time::Instant::now();
+            let output = async_command_output_with_timeout(cmd, build_timeout()).await?;
+            let cargo_wall_time = start.elapsed();
+
+            if let Some((ref mut processor, scenario, scenario_str, patch)) = self.processor_etc {
+                let data = ProcessOutputData {
+                    name: self.processor_name.clone(),
+                    cwd: self.cwd,
+                    profile: self.profile,
+                    scenario,
+                    scenario_str,
+                    patch,
+                    backend: self.backend,
+                    cargo_wall_time,
+                };
+                processor.process_output(&data, output).await?;
+            }
+            return Ok(());
+        }
+
         loop {
             // Make sure that Cargo.lock isn't changed by the build
             let _guard = EnsureImmutableFile::new(
@@ -318,11 +441,39 @@ impl<'a> CargoProcess<'a> {
                     }
                 };
 
+            // `cargo rustc` only accepts a single target package, so
+            // whole-workspace benchmarks use the equivalent workspace-wide
+            // subcommand and reach each crate's rustc through the environment
+            // rather than the trailing-args mechanism below.
+            let cargo_subcommand = if self.whole_workspace {
+                if !matches!(self.backend, CodegenBackend::Llvm) {
+                    return Err(anyhow::anyhow!(
+                        "whole-workspace benchmarks currently support only the LLVM backend"
+                    ));
+                }
+                match (cargo_subcommand, self.profile) {
+                    ("rustdoc", _) => "doc",
+                    ("rustc", Profile::Check) => "check",
+                    ("rustc", Profile::Clippy) => "clippy",
+                    ("rustc", _) => "build",
+                    (other, _) => other,
+                }
+            } else {
+                cargo_subcommand
+            };
+
             let mut cmd = self.base_command(self.cwd, cargo_subcommand);
-            cmd.arg("-p").arg(self.get_pkgid(self.cwd)?);
+            if self.whole_workspace {
+                cmd.arg("--workspace");
+            } else {
+                cmd.arg("-p").arg(self.get_pkgid(self.cwd)?);
+            }
             match self.profile {
                 Profile::Check => {
-                    cmd.arg("--profile").arg("check");
+                    // `cargo check` implies the check profile already.
+                    if !self.whole_workspace {
+                        cmd.arg("--profile").arg("check");
+                    }
                 }
                 Profile::Debug => {}
                 Profile::Doc => {}
@@ -336,12 +487,14 @@ impl<'a> CargoProcess<'a> {
                 cmd.arg("-Zunstable-options");
                 cmd.arg("-Ztimings");
             }
-            cmd.arg("--");
+            if !self.whole_workspace {
+                cmd.arg("--");
 
-            match self.backend {
-                CodegenBackend::Llvm => {}
-                CodegenBackend::Cranelift => {
-                    cmd.arg("-Zcodegen-backend=cranelift");
+                match self.backend {
+                    CodegenBackend::Llvm => {}
+                    CodegenBackend::Cranelift => {
+                        cmd.arg("-Zcodegen-backend=cranelift");
+                    }
                 }
             }
 
@@ -358,15 +511,25 @@ impl<'a> CargoProcess<'a> {
                     .map(|v| &mut v.0)
                     .expect("needs_final needs a processor");
                 let perf_tool_name = processor.perf_tool().name();
-                // If we're using a processor, we expect that only the crate
-                // we're interested in benchmarking will be built, not any
-                // dependencies.
-                if !processor.perf_tool().calls_cargo_recursively() {
-                    cmd.env("EXPECT_ONLY_WRAPPED_RUSTC", "1");
+                if self.whole_workspace {
+                    // The environment reaches every rustc invocation, so each
+                    // workspace member is measured. rustc-fake emits one stat
+                    // block per crate and `process_stat_output` sums them.
+                    cmd.env("WRAP_RUSTC_WITH", perf_tool_name);
+                    if !self.rustc_args.is_empty() {
+                        cmd.env("RUSTFLAGS", self.rustc_args.join(" "));
+                    }
+                } else {
+                    // If we're using a processor, we expect that only the crate
+                    // we're interested in benchmarking will be built, not any
+                    // dependencies.
+                    if !processor.perf_tool().calls_cargo_recursively() {
+                        cmd.env("EXPECT_ONLY_WRAPPED_RUSTC", "1");
+                    }
+                    cmd.arg("--wrap-rustc-with");
+                    cmd.arg(perf_tool_name);
+                    cmd.args(&self.rustc_args);
                 }
-                cmd.arg("--wrap-rustc-with");
-                cmd.arg(perf_tool_name);
-                cmd.args(&self.rustc_args);
 
                 // If we're not going to be in a processor, then there's no
                 // point ensuring that we recompile anything -- that just wastes
@@ -387,19 +550,30 @@ impl<'a> CargoProcess<'a> {
                         ),
                     )?;
                 }
-            } else {
+            } else if !self.whole_workspace {
                 // If we're not going to record the final rustc, then there's
                 // absolutely no point in waiting for it to build. This will
                 // have the final rustc just immediately exit(0) without
                 // actually running it.
+                //
+                // For whole-workspace benchmarks the preparation pass instead
+                // builds everything; the final pass re-measures the workspace
+                // members after their sources have been touched.
                 cmd.arg("--skip-this-rustc");
             }
 
             if self.incremental {
-                cmd.arg("-C");
-                let mut incr_arg = std::ffi::OsString::from("incremental=");
-                incr_arg.push(self.cwd.join("incremental-state"));
-                cmd.arg(incr_arg);
+                if self.whole_workspace {
+                    // -Cincremental can only be passed to a single crate via
+                    // the trailing args, so rely on cargo's own per-crate
+                    // incremental directories instead.
+                    cmd.env("CARGO_INCREMENTAL", "1");
+                } else {
+                    cmd.arg("-C");
+                    let mut incr_arg = std::ffi::OsString::from("incremental=");
+                    incr_arg.push(self.cwd.join("incremental-state"));
+                    cmd.arg(incr_arg);
+                }
             }
 
             if let Some(client) = &self.jobserver {
@@ -408,8 +582,19 @@ impl<'a> CargoProcess<'a> {
 
             log::debug!("{:?}", cmd);
 
+            if should_dump_commands() {
+                // `Command`'s Debug output includes the environment variables
+                // explicitly set on it, which is everything that matters here
+                // since the rest is inherited unchanged. The working directory
+                // is printed separately because it lives on `self`.
+                println!("cd {} && {:?}", self.cwd.display(), cmd);
+                return Ok(());
+            }
+
             let cmd = tokio::process::Command::from(cmd);
-            let output = async_command_output(cmd).await?;
+            let start = std::time::Instant::now();
+            let output = async_command_output_with_timeout(cmd, build_timeout()).await?;
+            let cargo_wall_time = start.elapsed();
 
             if let Some((ref mut processor, scenario, scenario_str, patch)) = self.processor_etc {
                 let data = ProcessOutputData {
@@ -420,6 +605,7 @@ impl<'a> CargoProcess<'a> {
                     scenario_str,
                     patch,
                     backend: self.backend,
+                    cargo_wall_time,
                 };
                 match processor.process_output(&data, output).await {
                     Ok(Retry::No) => return Ok(()),
@@ -484,6 +670,10 @@ pub struct ProcessOutputData<'a> {
     scenario_str: &'a str,
     patch: Option<&'a Patch>,
     backend: CodegenBackend,
+    /// Wall time of the whole cargo invocation that produced this output.
+    /// Subtracting rustc's own `wall-time` from it exposes cargo's
+    /// scheduling/metadata overhead.
+    cargo_wall_time: std::time::Duration,
 }
 
 /// Trait used by `Benchmark::measure()` to provide different kinds of
@@ -504,6 +694,11 @@ pub trait Processor {
         Box::pin(async {})
     }
 
+    /// Called before each timed iteration. Iterations with `warmup == true`
+    /// are executed for their side effects (warming up caches, stabilizing
+    /// CPU frequency) only; their measurements must not be recorded.
+    fn start_iteration(&mut self, _warmup: bool) {}
+
     /// Provided to permit switching on more expensive profiling if it's needed
     /// for the "first" run for any given benchmark (we reuse the processor),
     /// e.g. disabling -Zself-profile.
@@ -517,6 +712,59 @@ pub trait Processor {
     fn finished_first_collection(&mut self) -> bool {
         false
     }
+
+    /// Called after all iterations of one (codegen backend, profile)
+    /// configuration have been measured. Acts as a checkpoint boundary:
+    /// everything recorded so far belongs to completed configurations, so
+    /// processors can e.g. reset per-configuration state here.
+    fn finished_configuration(&mut self, _backend: CodegenBackend, _profile: Profile) {}
+
+    /// Called with each timing directory once its scenarios have run, as an
+    /// extension point for verifying that the benchmark left the environment
+    /// clean (e.g. no stray profiler output files). The default does nothing;
+    /// an error fails the benchmark.
+    fn verify_clean(&self, _cwd: &Path) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Bounds how often a flaky measurement is retried before the benchmark is
+/// recorded as errored instead of looping forever.
+pub struct RetryBudget {
+    tries: u8,
+    max_retries: u8,
+}
+
+impl RetryBudget {
+    pub fn new(max_retries: u8) -> Self {
+        RetryBudget {
+            tries: 0,
+            max_retries,
+        }
+    }
+
+    /// Notes a failed try. Returns true if another attempt is allowed.
+    pub fn try_again(&mut self) -> bool {
+        if self.tries < self.max_retries {
+            self.tries += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The number of failed tries so far.
+    pub fn tries(&self) -> u8 {
+        self.tries
+    }
+
+    pub fn max_retries(&self) -> u8 {
+        self.max_retries
+    }
+
+    pub fn reset(&mut self) {
+        self.tries = 0;
+    }
 }
 
 fn store_documentation_size_into_stats(stats: &mut Stats, doc_dir: &Path) {
@@ -535,9 +783,7 @@ fn store_documentation_size_into_stats(stats: &mut Stats, doc_dir: &Path) {
 
 fn store_artifact_sizes_into_stats(stats: &mut Stats, profile: &SelfProfile) {
     for artifact in profile.artifact_sizes.iter() {
-        stats
-            .stats
-            .insert(format!("size:{}", artifact.label), artifact.value as f64);
+        stats.insert(format!("size:{}", artifact.label), artifact.value as f64);
     }
 }
 
@@ -547,6 +793,8 @@ enum DeserializeStatError {
     NoOutput(process::Output),
     #[error("could not parse `{}` as a float", .0)]
     ParseError(String, #[source] ::std::num::ParseFloatError),
+    #[error("measurement of `{name}` only active for {pct}% of the time")]
+    PartialMeasurement { name: String, pct: String },
     #[error("could not process xperf data")]
     XperfError(#[from] anyhow::Error),
     #[error("io error")]
@@ -557,6 +805,46 @@ enum SelfProfileFiles {
     Eight { file: PathBuf },
 }
 
+/// A single counter as emitted by `perf stat -j`.
+#[derive(serde::Deserialize)]
+struct PerfStatJsonLine {
+    /// The counter value; numeric, but formatted as a string by perf.
+    /// May also be `<not supported>` or `<not counted>`.
+    #[serde(rename = "counter-value")]
+    counter_value: String,
+    event: String,
+    /// Percentage of time the counter was actually running. Some perf
+    /// versions emit `null` here when the counter never ran; map that to 0.0
+    /// so the line still deserializes (and is then skipped as under-measured).
+    #[serde(rename = "pcnt-running", deserialize_with = "crate::null_means_nan")]
+    pcnt_running: f64,
+}
+
+/// Aliases under which `perf` may report an event we collect, mapped to the
+/// canonical name used in the database. The exact spelling varies with the
+/// perf version, kernel config and CPU: hybrid CPUs (Alder Lake and later)
+/// wrap P-core events in `cpu_core/.../`, and the privilege-level suffix can
+/// be dropped (`instructions`) or widened (`instructions:uk`) depending on
+/// how `perf_event_paranoid` resolves. Without normalization each spelling
+/// starts a fresh time series, so historical graphs break across perf
+/// upgrades.
+const STAT_NAME_ALIASES: &[(&str, &str)] = &[
+    ("cpu_core/instructions:u/", "instructions:u"),
+    ("cpu_core/cycles:u/", "cycles:u"),
+    ("instructions", "instructions:u"),
+    ("instructions:uk", "instructions:u"),
+    // Bare `cycles` is deliberately absent: it is its own metric, emitted by
+    // the ETW path on Windows, and must not be folded into `cycles:u`.
+    ("cycles:uk", "cycles:u"),
+];
+
+fn canonicalize_stat_name(name: &str) -> &str {
+    STAT_NAME_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, canonical)| canonical)
+}
+
 fn process_stat_output(
     output: process::Output,
 ) -> Result<(Stats, Option<SelfProfile>, Option<SelfProfileFiles>), DeserializeStatError> {
@@ -601,6 +889,40 @@ fn process_stat_output(
             continue;
         }
 
+        // Output from `perf stat -j` (opt-in via `RUSTC_PERF_STAT_JSON` in
+        // rustc-fake); one JSON object per line.
+        if line.starts_with('{') {
+            let parsed: PerfStatJsonLine = match serde_json::from_str(line) {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    log::warn!("unhandled JSON line: {} ({})", line, error);
+                    continue;
+                }
+            };
+            let name = canonicalize_stat_name(parsed.event.as_str());
+            if parsed.counter_value == "<not supported>"
+                || parsed.counter_value == "<not counted>"
+                || parsed.counter_value.is_empty()
+            {
+                continue;
+            }
+            if parsed.pcnt_running < 100.0 {
+                log::warn!(
+                    "skipping `{}`: measurement only active for {}% of the time",
+                    name,
+                    parsed.pcnt_running
+                );
+                continue;
+            }
+            stats.accumulate(
+                name.to_owned(),
+                parsed.counter_value.parse().map_err(|e| {
+                    DeserializeStatError::ParseError(parsed.counter_value.clone(), e)
+                })?,
+            );
+            continue;
+        }
+
         // github.com/torvalds/linux/blob/bc78d646e708/tools/perf/Documentation/perf-stat.txt#L281
         macro_rules! get {
             ($e: expr) => {
@@ -616,23 +938,24 @@ fn process_stat_output(
         let mut parts = line.split(';').map(|s| s.trim());
         let cnt = get!(parts.next());
         let _unit = get!(parts.next());
-        let mut name = get!(parts.next());
-        // Map P-core events to normal events
-        if name == "cpu_core/instructions:u/" {
-            name = "instructions:u";
-        }
+        let name = canonicalize_stat_name(get!(parts.next()));
         let _time = get!(parts.next());
         let pct = get!(parts.next());
         if cnt == "<not supported>" || cnt == "<not counted>" || cnt.is_empty() {
             continue;
         }
+        // On a contended machine the counter may get multiplexed; that run's
+        // numbers are useless, but the condition is transient, so surface it
+        // as a recoverable error and let the caller retry.
         if !pct.starts_with("100.") {
-            panic!(
-                "measurement of `{}` only active for {}% of the time",
-                name, pct
-            );
+            return Err(DeserializeStatError::PartialMeasurement {
+                name: name.to_string(),
+                pct: pct.to_string(),
+            });
         }
-        stats.insert(
+        // Summing here makes whole-workspace runs work: their output contains
+        // one `perf stat` block per compiled crate.
+        stats.accumulate(
             name.to_owned(),
             cnt.parse()
                 .map_err(|e| DeserializeStatError::ParseError(cnt.to_string(), e))?,
@@ -659,9 +982,43 @@ fn process_stat_output(
     Ok((stats, profile, files))
 }
 
+/// Identifier of a collected stat: either a metric the database knows about,
+/// or a raw name for anything else (e.g. the dynamic `size:` artifact
+/// labels). Known stats are kept typed so that references to them cannot be
+/// typo'd; the wire format stays the stat's string name either way.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StatId {
+    Known(Metric),
+    Other(String),
+}
+
+impl StatId {
+    pub fn name(&self) -> &str {
+        match self {
+            StatId::Known(metric) => metric.as_str(),
+            StatId::Other(name) => name,
+        }
+    }
+}
+
+impl From<String> for StatId {
+    fn from(name: String) -> Self {
+        match name.parse::<Metric>() {
+            Ok(metric) => StatId::Known(metric),
+            Err(_) => StatId::Other(name),
+        }
+    }
+}
+
+impl From<&str> for StatId {
+    fn from(name: &str) -> Self {
+        StatId::from(name.to_string())
+    }
+}
+
 #[derive(Clone)]
 pub struct Stats {
-    stats: HashMap<String, f64>,
+    stats: HashMap<StatId, f64>,
 }
 
 impl Default for Stats {
@@ -678,7 +1035,7 @@ impl Stats {
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> + '_ {
-        self.stats.iter().map(|(k, v)| (k.as_str(), *v))
+        self.stats.iter().map(|(k, v)| (k.name(), *v))
     }
 
     pub fn is_empty(&self) -> bool {
@@ -686,7 +1043,70 @@ impl Stats {
     }
 
     pub fn insert(&mut self, stat: String, value: f64) {
-        self.stats.insert(stat, value);
+        self.stats.insert(StatId::from(stat), value);
+    }
+
+    /// Like [`Stats::insert`], but folds repeated occurrences of a stat
+    /// together instead of overwriting: values are summed, except `max-rss`
+    /// where the peak is kept. A single rustc invocation emits each stat
+    /// once, so this only matters for whole-workspace benchmarks, whose
+    /// output contains one block per compiled crate.
+    pub fn accumulate(&mut self, stat: String, value: f64) {
+        let keep_max = stat == "max-rss";
+        let entry = self.stats.entry(StatId::from(stat)).or_insert(0.0);
+        if keep_max {
+            *entry = entry.max(value);
+        } else {
+            *entry += value;
+        }
+    }
+
+    pub fn get(&self, stat: &str) -> Option<f64> {
+        self.stats.get(&StatId::from(stat)).copied()
+    }
+
+    /// The stats ordered by name, for deterministic iteration and cheap
+    /// repeated lookups.
+    pub fn as_sorted_map(&self) -> BTreeMap<String, f64> {
+        self.stats
+            .iter()
+            .map(|(k, v)| (k.name().to_string(), *v))
+            .collect()
+    }
+
+    /// Returns true for stats whose ideal value is the same on every run, so
+    /// that any difference between iterations is measurement noise (mostly
+    /// interrupts and context switches inflating the counters). For these the
+    /// per-stat *minimum* across iterations is the best estimate of the true
+    /// cost. Wall-time and the various clocks genuinely vary between runs and
+    /// are excluded, as are the `size:` artifact stats (identical each run,
+    /// but min vs. average makes no difference for them).
+    pub fn is_deterministic(stat: &str) -> bool {
+        matches!(
+            stat,
+            "instructions:u" | "cycles:u" | "faults" | "branch-misses" | "cache-misses"
+        )
+    }
+
+    /// Folds `other` into `self`, keeping the per-stat minimum for
+    /// deterministic stats (see [`Stats::is_deterministic`]) and the newest
+    /// value for all others. Stats present on only one side are kept as-is.
+    pub fn combine_min(&mut self, other: Stats) {
+        for (stat, value) in other.stats {
+            match self.stats.entry(stat) {
+                std::collections::hash_map::Entry::Occupied(mut entry) => {
+                    if Self::is_deterministic(entry.key().name()) {
+                        let min = entry.get().min(value);
+                        entry.insert(min);
+                    } else {
+                        entry.insert(value);
+                    }
+                }
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+            }
+        }
     }
 }
 
@@ -750,3 +1170,91 @@ fn parse_self_profile(
     };
     Ok((profile, files))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{process_stat_output, DeserializeStatError, PerfStatJsonLine, RetryBudget, Stats};
+
+    #[test]
+    fn stat_names_are_canonicalized() {
+        use super::canonicalize_stat_name;
+        assert_eq!(canonicalize_stat_name("instructions"), "instructions:u");
+        assert_eq!(canonicalize_stat_name("instructions:uk"), "instructions:u");
+        assert_eq!(
+            canonicalize_stat_name("cpu_core/instructions:u/"),
+            "instructions:u"
+        );
+        assert_eq!(canonicalize_stat_name("instructions:u"), "instructions:u");
+        // `cycles` without a suffix is the ETW metric, not a perf alias.
+        assert_eq!(canonicalize_stat_name("cycles"), "cycles");
+        assert_eq!(canonicalize_stat_name("wall-time"), "wall-time");
+    }
+
+    #[test]
+    fn accumulate_sums_stats_but_keeps_peak_rss() {
+        let mut stats = Stats::new();
+        stats.accumulate("instructions:u".to_string(), 1000.0);
+        stats.accumulate("instructions:u".to_string(), 500.0);
+        stats.accumulate("max-rss".to_string(), 4096.0);
+        stats.accumulate("max-rss".to_string(), 1024.0);
+        assert_eq!(stats.get("instructions:u"), Some(1500.0));
+        assert_eq!(stats.get("max-rss"), Some(4096.0));
+    }
+
+    #[test]
+    fn combine_min_takes_minimum_only_for_deterministic_stats() {
+        let mut combined = Stats::new();
+        for (instructions, wall_time) in [(1010.0, 2.0), (1000.0, 3.0), (1005.0, 2.5)] {
+            let mut iteration = Stats::new();
+            iteration.insert("instructions:u".to_string(), instructions);
+            iteration.insert("wall-time".to_string(), wall_time);
+            combined.combine_min(iteration);
+        }
+        // The cleanest run wins for instruction counts...
+        assert_eq!(combined.get("instructions:u"), Some(1000.0));
+        // ...while wall-time keeps the last iteration's value.
+        assert_eq!(combined.get("wall-time"), Some(2.5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn partial_measurement_is_recoverable() {
+        use std::os::unix::process::ExitStatusExt;
+
+        // A `perf stat -x;` line where the counter was only running 97% of
+        // the time due to multiplexing.
+        let output = std::process::Output {
+            status: std::process::ExitStatus::from_raw(0),
+            stdout: b"1000;;instructions:u;800000;97.00\n".to_vec(),
+            stderr: Vec::new(),
+        };
+        match process_stat_output(output) {
+            Err(DeserializeStatError::PartialMeasurement { name, pct }) => {
+                assert_eq!(name, "instructions:u");
+                assert_eq!(pct, "97.00");
+            }
+            other => panic!("expected PartialMeasurement, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn null_pcnt_running_deserializes() {
+        let line: PerfStatJsonLine = serde_json::from_str(
+            r#"{"counter-value": "1000", "event": "instructions:u", "pcnt-running": null}"#,
+        )
+        .unwrap();
+        assert_eq!(line.pcnt_running, 0.0);
+    }
+
+    #[test]
+    fn retry_budget_is_exhaustible() {
+        let mut budget = RetryBudget::new(3);
+        assert!(budget.try_again());
+        assert!(budget.try_again());
+        assert!(budget.try_again());
+        // The budget is spent; the caller must give up rather than loop.
+        assert!(!budget.try_again());
+        budget.reset();
+        assert!(budget.try_again());
+    }
+}
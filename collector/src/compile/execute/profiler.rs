@@ -1,3 +1,4 @@
+use crate::compile::benchmark::profile::Profile;
 use crate::compile::execute::{PerfTool, ProcessOutputData, Processor, Retry};
 use crate::utils::cachegrind::{cachegrind_annotate, cachegrind_diff};
 use crate::utils::diff::run_diff;
@@ -17,20 +18,55 @@ use std::{fs, io, process};
 #[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
 pub enum Profiler {
     SelfProfile,
+    /// Like `SelfProfile`, but stops after `summarize --json`: the raw
+    /// `Zsp.*` data files are kept and a single JSON export is produced,
+    /// without requiring `flamegraph` or `crox` on PATH.
+    SelfProfileJson,
     PerfRecord,
+    /// Runs rustc under `perf stat record`, producing the usual counter
+    /// values *and* a sampled profile in one invocation. Convenient for quick
+    /// investigations where both the headline counts and a `perf report` are
+    /// wanted; the sampling machinery adds a small overhead to the counts, so
+    /// they are not comparable with regular measurement runs.
+    PerfStatRecord,
+    /// Like `PerfRecord`, but records LBR branch samples (`perf record -j
+    /// any,u -e cycles:u`) in the format consumed by BOLT/Propeller. Requires
+    /// a CPU with LBR support (recent Intel, or AMD Zen 3+).
+    PerfRecordBolt,
     Oprofile,
     Samply,
+    /// Runs rustc under Apple's `xctrace` (Instruments) with the Time
+    /// Profiler template, producing a `.trace` bundle that can be opened in
+    /// Instruments. The native analog of `perf-record` for macOS; it errors
+    /// out on other platforms.
+    Instruments,
     Cachegrind,
     Callgrind,
+    /// Like `Callgrind`, but additionally records per-instruction costs and
+    /// conditional/indirect jumps (`--dump-instr=yes --collect-jumps=yes`),
+    /// so the copied `clgout` shows a usable call graph when loaded into
+    /// KCachegrind. The extra collection makes runs noticeably slower than
+    /// the counts-only `Callgrind` mode (roughly 2x on top of its ~20x), so
+    /// the lightweight mode remains the default choice for plain counts.
+    CallgrindGraph,
     Dhat,
     DhatCopy,
     Massif,
+    /// Generic valgrind passthrough: runs rustc under `valgrind` with the
+    /// flags given verbatim in the `VALGRIND_ARGS` environment variable, for
+    /// one-off experiments (e.g. `--tool=exp-bbv`, or cachegrind with cache
+    /// simulation enabled) without a dedicated hardcoded variant. The user is
+    /// responsible for arranging output capture via the flags they pass; if a
+    /// file named `valgrind-out` is produced, it is copied to the output dir.
+    ValgrindRaw,
     Bytehound,
     Eprintln,
     LlvmLines,
     MonoItems,
     DepGraph,
+    CrateGraph,
     LlvmIr,
+    ArtifactSize,
 }
 
 impl Profiler {
@@ -41,6 +77,7 @@ impl Profiler {
             self,
             Profiler::Cachegrind
                 | Profiler::Callgrind
+                | Profiler::CallgrindGraph
                 | Profiler::Dhat
                 | Profiler::DhatCopy
                 | Profiler::Massif
@@ -50,6 +87,8 @@ impl Profiler {
                 | Profiler::LlvmIr
                 | Profiler::MonoItems
                 | Profiler::DepGraph
+                | Profiler::CrateGraph
+                | Profiler::ArtifactSize
         )
     }
 
@@ -59,9 +98,12 @@ impl Profiler {
         match self {
             Cachegrind => "cgout",
             DepGraph => "dep-graph",
+            CrateGraph => "depgraph",
+            ArtifactSize => "artifact-size",
 
-            SelfProfile | PerfRecord | Oprofile | Samply | Callgrind | Dhat | DhatCopy | Massif
-            | Bytehound | Eprintln | LlvmLines | MonoItems | LlvmIr => "",
+            SelfProfile | SelfProfileJson | PerfRecord | PerfStatRecord | PerfRecordBolt
+            | Oprofile | Samply | Instruments | Callgrind | CallgrindGraph | Dhat | DhatCopy
+            | Massif | ValgrindRaw | Bytehound | Eprintln | LlvmLines | MonoItems | LlvmIr => "",
         }
     }
 
@@ -72,8 +114,10 @@ impl Profiler {
             Cachegrind => "",
             DepGraph => ".txt",
 
-            SelfProfile | PerfRecord | Oprofile | Samply | Callgrind | Dhat | DhatCopy | Massif
-            | Bytehound | Eprintln | LlvmLines | MonoItems | LlvmIr => "",
+            SelfProfile | SelfProfileJson | PerfRecord | PerfStatRecord | PerfRecordBolt
+            | Oprofile | Samply | Instruments | Callgrind | CallgrindGraph | Dhat | DhatCopy
+            | Massif | ValgrindRaw | Bytehound | Eprintln | LlvmLines | MonoItems | CrateGraph
+            | LlvmIr | ArtifactSize => "",
         }
     }
 
@@ -83,25 +127,115 @@ impl Profiler {
         match self {
             Cachegrind => cachegrind_diff(left, right, output),
             DepGraph => run_diff(left, right, output),
+            CrateGraph => run_diff(left, right, output),
+            ArtifactSize => run_diff(left, right, output),
 
-            SelfProfile | PerfRecord | Oprofile | Samply | Callgrind | Dhat | DhatCopy | Massif
-            | Bytehound | Eprintln | LlvmLines | MonoItems | LlvmIr => Ok(()),
+            SelfProfile | SelfProfileJson | PerfRecord | PerfStatRecord | PerfRecordBolt
+            | Oprofile | Samply | Instruments | Callgrind | CallgrindGraph | Dhat | DhatCopy
+            | Massif | ValgrindRaw | Bytehound | Eprintln | LlvmLines | MonoItems | LlvmIr => {
+                Ok(())
+            }
         }
     }
 }
 
+/// Strips a copy of the given artifact and returns the resulting size, or
+/// `None` if `strip` is unavailable or fails (e.g. on rlibs containing
+/// non-object members).
+fn stripped_size(path: &Path) -> Option<u64> {
+    let tmp = tempfile::NamedTempFile::new().ok()?;
+    let status = Command::new("strip")
+        .arg("-o")
+        .arg(tmp.path())
+        .arg(path)
+        .status()
+        .ok()?;
+    if !status.success() {
+        return None;
+    }
+    tmp.path().metadata().ok().map(|m| m.len())
+}
+
+/// The default output filename scheme, used when no `--name-template` is
+/// given. Each output file additionally gets a profiler-specific prefix.
+pub const DEFAULT_NAME_TEMPLATE: &str = "{prefix}-{id}-{benchmark}-{profile}-{scenario}";
+
+/// The placeholders understood by `--name-template`.
+const NAME_TEMPLATE_PLACEHOLDERS: &[&str] =
+    &["prefix", "id", "benchmark", "profile", "scenario", "ts"];
+
+/// Checks that a `--name-template` only contains known placeholders, so that
+/// a typo errors out before any (potentially slow) profiling starts.
+pub fn validate_name_template(template: &str) -> anyhow::Result<()> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let tail = &rest[start + 1..];
+        let end = tail
+            .find('}')
+            .ok_or_else(|| anyhow::anyhow!("unclosed `{{` in name template `{}`", template))?;
+        let placeholder = &tail[..end];
+        if !NAME_TEMPLATE_PLACEHOLDERS.contains(&placeholder) {
+            anyhow::bail!(
+                "unknown placeholder `{{{}}}` in name template `{}`; known placeholders: {}",
+                placeholder,
+                template,
+                NAME_TEMPLATE_PLACEHOLDERS
+                    .iter()
+                    .map(|p| format!("{{{}}}", p))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        rest = &tail[end + 1..];
+    }
+    Ok(())
+}
+
+/// How profiler artifacts are arranged within the output directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum OutputLayout {
+    /// Everything goes directly into the output directory, with long
+    /// hyphenated names built from `--name-template`. The historical
+    /// behavior, and what the diffing machinery expects.
+    #[default]
+    Flat,
+    /// Artifacts are placed under
+    /// `<id>/<benchmark>/<profile>/<scenario>/` with short names (just the
+    /// artifact tag, e.g. `perf` or `cgann`). Easier to navigate for large
+    /// sessions; `--name-template` is ignored.
+    Nested,
+}
+
 pub struct ProfileProcessor<'a> {
     profiler: Profiler,
     output_dir: &'a Path,
     id: &'a str,
+    name_template: &'a str,
+    layout: OutputLayout,
+    /// Timestamp substituted for `{ts}`; captured once so all files of one
+    /// profiling session share it.
+    timestamp: String,
+    /// The last directory we created, so that the (nested) per-case
+    /// directories are only `create_dir_all`ed once each.
+    created_dir: Option<std::path::PathBuf>,
 }
 
 impl<'a> ProfileProcessor<'a> {
-    pub fn new(profiler: Profiler, output_dir: &'a Path, id: &'a str) -> Self {
+    pub fn new(
+        profiler: Profiler,
+        output_dir: &'a Path,
+        id: &'a str,
+        name_template: Option<&'a str>,
+        layout: OutputLayout,
+    ) -> Self {
         ProfileProcessor {
             profiler,
             output_dir,
             id,
+            name_template: name_template.unwrap_or(DEFAULT_NAME_TEMPLATE),
+            layout,
+            timestamp: chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string(),
+            created_dir: None,
         }
     }
 }
@@ -117,14 +251,39 @@ impl Processor for ProfileProcessor<'_> {
         output: process::Output,
     ) -> Pin<Box<dyn Future<Output = anyhow::Result<Retry>> + 'b>> {
         Box::pin(async move {
-            fs::create_dir_all(self.output_dir)?;
+            // The directory this test case's artifacts go into. In the flat
+            // layout that is the output directory itself; in the nested
+            // layout each case gets its own subtree.
+            let case_dir = match self.layout {
+                OutputLayout::Flat => self.output_dir.to_path_buf(),
+                OutputLayout::Nested => self
+                    .output_dir
+                    .join(self.id)
+                    .join(data.name.to_string())
+                    .join(format!("{:?}", data.profile))
+                    .join(data.scenario_str),
+            };
+            if self.created_dir.as_deref() != Some(&case_dir) {
+                fs::create_dir_all(&case_dir)?;
+                self.created_dir = Some(case_dir.clone());
+            }
 
-            // Produce a name of the form $PREFIX-$ID-$BENCHMARK-$PROFILE-$SCENARIO.
+            // Produce a name according to the template, which by default is of
+            // the form $PREFIX-$ID-$BENCHMARK-$PROFILE-$SCENARIO. In the
+            // nested layout the directory already encodes all of that, so the
+            // artifact tag alone is used as the file name.
             let out_file = |prefix: &str| -> String {
-                format!(
-                    "{}-{}-{}-{:?}-{}",
-                    prefix, self.id, data.name, data.profile, data.scenario_str
-                )
+                match self.layout {
+                    OutputLayout::Flat => self
+                        .name_template
+                        .replace("{prefix}", prefix)
+                        .replace("{id}", self.id)
+                        .replace("{benchmark}", &data.name.to_string())
+                        .replace("{profile}", &format!("{:?}", data.profile))
+                        .replace("{scenario}", data.scenario_str)
+                        .replace("{ts}", &self.timestamp),
+                    OutputLayout::Nested => prefix.to_string(),
+                }
             };
 
             // Combine a dir and a file.
@@ -142,13 +301,15 @@ impl Processor for ProfileProcessor<'_> {
                 // as `Zsp.{events,string_data,string_index}` in the process, then
                 // post-process them with `summarize`, `flamegraph`, and `crox` to
                 // produce several data files in the output dir.
-                Profiler::SelfProfile => {
+                //
+                // `SelfProfileJson` shares the data collection and renaming, but
+                // only runs `summarize --json`, leaving flamegraph/crox (and the
+                // need for them on PATH) out.
+                Profiler::SelfProfile | Profiler::SelfProfileJson => {
                     let tmp_zsp_dir = filepath(data.cwd, "Zsp");
-                    let zsp_dir = filepath(self.output_dir, &out_file("Zsp"));
+                    let zsp_dir = filepath(&case_dir, &out_file("Zsp"));
                     let zsp_files_prefix = filepath(&zsp_dir, "Zsp");
-                    let summarize_file = filepath(self.output_dir, &out_file("summarize"));
-                    let flamegraph_file = filepath(self.output_dir, &out_file("flamegraph"));
-                    let crox_file = filepath(self.output_dir, &out_file("crox"));
+                    let summarize_file = filepath(&case_dir, &out_file("summarize"));
 
                     // Move the directory.
                     if zsp_dir.exists() {
@@ -180,21 +341,30 @@ impl Processor for ProfileProcessor<'_> {
 
                     // Run `summarize`.
                     let mut summarize_cmd = Command::new("summarize");
-                    summarize_cmd.arg("summarize").arg(&zsp_files_prefix);
+                    summarize_cmd.arg("summarize");
+                    if let Profiler::SelfProfileJson = self.profiler {
+                        summarize_cmd.arg("--json");
+                    }
+                    summarize_cmd.arg(&zsp_files_prefix);
                     summarize_cmd.stdout(File::create(summarize_file)?);
                     summarize_cmd.status().context("summarize")?;
 
-                    // Run `flamegraph`.
-                    let mut flamegraph_cmd = Command::new("flamegraph");
-                    flamegraph_cmd.arg(&zsp_files_prefix);
-                    flamegraph_cmd.status().context("flamegraph")?;
-                    utils::fs::rename("rustc.svg", flamegraph_file)?;
-
-                    // Run `crox`.
-                    let mut crox_cmd = Command::new("crox");
-                    crox_cmd.arg(&zsp_files_prefix);
-                    crox_cmd.status().context("crox")?;
-                    utils::fs::rename("chrome_profiler.json", crox_file)?;
+                    if let Profiler::SelfProfile = self.profiler {
+                        let flamegraph_file = filepath(&case_dir, &out_file("flamegraph"));
+                        let crox_file = filepath(&case_dir, &out_file("crox"));
+
+                        // Run `flamegraph`.
+                        let mut flamegraph_cmd = Command::new("flamegraph");
+                        flamegraph_cmd.arg(&zsp_files_prefix);
+                        flamegraph_cmd.status().context("flamegraph")?;
+                        utils::fs::rename("rustc.svg", flamegraph_file)?;
+
+                        // Run `crox`.
+                        let mut crox_cmd = Command::new("crox");
+                        crox_cmd.arg(&zsp_files_prefix);
+                        crox_cmd.status().context("crox")?;
+                        utils::fs::rename("chrome_profiler.json", crox_file)?;
+                    }
                 }
 
                 // perf-record produces (via rustc-fake) a data file called `perf`.
@@ -202,7 +372,34 @@ impl Processor for ProfileProcessor<'_> {
                 // name in the process.
                 Profiler::PerfRecord => {
                     let tmp_perf_file = filepath(data.cwd, "perf");
-                    let perf_file = filepath(self.output_dir, &out_file("perf"));
+                    let perf_file = filepath(&case_dir, &out_file("perf"));
+
+                    fs::copy(tmp_perf_file, perf_file)?;
+                }
+
+                // perf-stat-record leaves its sampled data in `perf.data`
+                // (copied to the output dir for later `perf report`) and
+                // prints the usual counter lines to stdout, which we parse
+                // and write next to the profile as a JSON stats file.
+                Profiler::PerfStatRecord => {
+                    let tmp_perf_file = filepath(data.cwd, "perf.data");
+                    let perf_file = filepath(&case_dir, &out_file("perf"));
+                    fs::copy(tmp_perf_file, perf_file)?;
+
+                    let (stats, _, _) = super::process_stat_output(output).map_err(|error| {
+                        anyhow::anyhow!("failed to parse perf stat output: {error}")
+                    })?;
+                    let stats_file = filepath(&case_dir, &out_file("stats"));
+                    let mut file = io::BufWriter::new(File::create(stats_file)?);
+                    serde_json::to_writer_pretty(&mut file, &stats.as_sorted_map())?;
+                    file.flush()?;
+                }
+
+                // Like perf-record, but the data file contains LBR branch
+                // samples suitable for feeding into BOLT.
+                Profiler::PerfRecordBolt => {
+                    let tmp_perf_file = filepath(data.cwd, "perf");
+                    let perf_file = filepath(&case_dir, &out_file("perf-bolt"));
 
                     fs::copy(tmp_perf_file, perf_file)?;
                 }
@@ -213,9 +410,9 @@ impl Processor for ProfileProcessor<'_> {
                 // twice to produce another two data files in the output dir.
                 Profiler::Oprofile => {
                     let tmp_opout_dir = filepath(data.cwd, "oprofile_data");
-                    let opout_dir = filepath(self.output_dir, &out_file("opout"));
-                    let oprep_file = filepath(self.output_dir, &out_file("oprep"));
-                    let opann_file = filepath(self.output_dir, &out_file("opann"));
+                    let opout_dir = filepath(&case_dir, &out_file("opout"));
+                    let oprep_file = filepath(&case_dir, &out_file("oprep"));
+                    let opann_file = filepath(&case_dir, &out_file("opann"));
 
                     // Move the directory.
                     if opout_dir.exists() {
@@ -254,19 +451,34 @@ impl Processor for ProfileProcessor<'_> {
                 // giving it a new name in the process.
                 Profiler::Samply => {
                     let tmp_samply_file = filepath(data.cwd, "profile.json");
-                    let samply_file = filepath(self.output_dir, &out_file("samply"));
+                    let samply_file = filepath(&case_dir, &out_file("samply"));
 
                     fs::copy(tmp_samply_file, samply_file)?;
                 }
 
+                // xctrace produces (via rustc-fake) a `.trace` bundle (a
+                // directory) called `instruments.trace`. We move it from the
+                // temp dir to the output dir, giving it a new name in the
+                // process.
+                Profiler::Instruments => {
+                    let tmp_trace_dir = filepath(data.cwd, "instruments.trace");
+                    let trace_dir =
+                        filepath(&case_dir, &format!("{}.trace", out_file("instruments")));
+
+                    if trace_dir.exists() {
+                        fs::remove_dir_all(&trace_dir)?;
+                    }
+                    utils::fs::rename(tmp_trace_dir, &trace_dir)?;
+                }
+
                 // Cachegrind produces (via rustc-fake) a data file called `cgout`.
                 // We copy it from the temp dir to the output dir, giving it a new
                 // name in the process, and then post-process it to produce another
                 // data file in the output dir.
                 Profiler::Cachegrind => {
                     let tmp_cgout_file = filepath(data.cwd, "cgout");
-                    let cgout_file = filepath(self.output_dir, &out_file("cgout"));
-                    let cgann_file = filepath(self.output_dir, &out_file("cgann"));
+                    let cgout_file = filepath(&case_dir, &out_file("cgout"));
+                    let cgann_file = filepath(&case_dir, &out_file("cgann"));
 
                     cachegrind_annotate(&tmp_cgout_file, &cgout_file, &cgann_file)?;
                 }
@@ -274,11 +486,13 @@ impl Processor for ProfileProcessor<'_> {
                 // Callgrind produces (via rustc-fake) a data file called `clgout`.
                 // We copy it from the temp dir to the output dir, giving it a new
                 // name in the process, and then post-process it to produce another
-                // data file in the output dir.
-                Profiler::Callgrind => {
+                // data file in the output dir. The graph variant only differs in
+                // the valgrind flags rustc-fake passes; the `clgout` it produces
+                // additionally loads usefully in KCachegrind.
+                Profiler::Callgrind | Profiler::CallgrindGraph => {
                     let tmp_clgout_file = filepath(data.cwd, "clgout");
-                    let clgout_file = filepath(self.output_dir, &out_file("clgout"));
-                    let clgann_file = filepath(self.output_dir, &out_file("clgann"));
+                    let clgout_file = filepath(&case_dir, &out_file("clgout"));
+                    let clgann_file = filepath(&case_dir, &out_file("clgann"));
 
                     fs::copy(tmp_clgout_file, &clgout_file)?;
 
@@ -296,7 +510,7 @@ impl Processor for ProfileProcessor<'_> {
                 // name in the process.
                 Profiler::Dhat => {
                     let tmp_dhout_file = filepath(data.cwd, "dhout");
-                    let dhout_file = filepath(self.output_dir, &out_file("dhout"));
+                    let dhout_file = filepath(&case_dir, &out_file("dhout"));
 
                     fs::copy(tmp_dhout_file, dhout_file)?;
                 }
@@ -306,17 +520,29 @@ impl Processor for ProfileProcessor<'_> {
                 // it a new name in the process.
                 Profiler::DhatCopy => {
                     let tmp_dhcopy_file = filepath(data.cwd, "dhcopy");
-                    let dhcopy_file = filepath(self.output_dir, &out_file("dhcopy"));
+                    let dhcopy_file = filepath(&case_dir, &out_file("dhcopy"));
 
                     fs::copy(tmp_dhcopy_file, dhcopy_file)?;
                 }
 
+                // A raw valgrind run may or may not produce output, depending
+                // on the flags in `VALGRIND_ARGS`. If the conventional
+                // `valgrind-out` file exists, copy it to the output dir; the
+                // user is otherwise responsible for collecting their output.
+                Profiler::ValgrindRaw => {
+                    let tmp_out_file = filepath(data.cwd, "valgrind-out");
+                    if tmp_out_file.exists() {
+                        let target_file = filepath(&case_dir, &out_file("valgrind-out"));
+                        fs::copy(tmp_out_file, target_file)?;
+                    }
+                }
+
                 // Massif produces (via rustc-fake) a data file called `msout`. We
                 // copy it from the temp dir to the output dir, giving it a new
                 // name in the process.
                 Profiler::Massif => {
                     let tmp_msout_file = filepath(data.cwd, "msout");
-                    let msout_file = filepath(self.output_dir, &out_file("msout"));
+                    let msout_file = filepath(&case_dir, &out_file("msout"));
 
                     fs::copy(tmp_msout_file, msout_file)?;
                 }
@@ -326,7 +552,7 @@ impl Processor for ProfileProcessor<'_> {
                 // it a new name in the process.
                 Profiler::Bytehound => {
                     let tmp_bytehound_file = filepath(data.cwd, "bytehound.dat");
-                    let target_file = filepath(self.output_dir, &out_file("bhout"));
+                    let target_file = filepath(&case_dir, &out_file("bhout"));
                     fs::copy(tmp_bytehound_file, target_file)?;
                 }
 
@@ -335,7 +561,7 @@ impl Processor for ProfileProcessor<'_> {
                 // dir, giving it a new name in the process.
                 Profiler::Eprintln => {
                     let tmp_eprintln_file = filepath(data.cwd, "eprintln");
-                    let eprintln_file = filepath(self.output_dir, &out_file("eprintln"));
+                    let eprintln_file = filepath(&case_dir, &out_file("eprintln"));
 
                     #[allow(dead_code)]
                     #[derive(serde::Deserialize)]
@@ -366,7 +592,7 @@ impl Processor for ProfileProcessor<'_> {
                 // dir, giving it a new name in the process.
                 Profiler::MonoItems => {
                     let tmp_file = filepath(data.cwd, "mono-items");
-                    let out_dir = self.output_dir.join(out_file("mono-items"));
+                    let out_dir = case_dir.join(out_file("mono-items"));
                     let _ = fs::create_dir_all(&out_dir);
                     let result_file = filepath(&out_dir, "raw");
 
@@ -409,32 +635,106 @@ impl Processor for ProfileProcessor<'_> {
 
                 Profiler::DepGraph => {
                     let tmp_file = filepath(data.cwd, "dep_graph.txt");
-                    let output =
-                        filepath(self.output_dir, &format!("{}.txt", out_file("dep-graph")));
+                    let output = filepath(&case_dir, &format!("{}.txt", out_file("dep-graph")));
 
                     fs::copy(tmp_file, output)?;
 
                     let tmp_file = filepath(data.cwd, "dep_graph.dot");
-                    let output =
-                        filepath(self.output_dir, &format!("{}.dot", out_file("dep-graph")));
+                    let output = filepath(&case_dir, &format!("{}.dot", out_file("dep-graph")));
 
                     // May not exist if not incremental, but then that's OK.
                     fs::copy(tmp_file, output)?;
                 }
 
+                // rustc-fake redirects the emitted (pre-optimization) LLVM IR
+                // to a file called `llvm-ir`. We copy it from the temp dir to
+                // the output dir, giving it a new name in the process, and
+                // also record its total size, which complements the
+                // line-count view of `LlvmLines` with a raw-size view.
                 Profiler::LlvmIr => {
                     let tmp_file = filepath(data.cwd, "llvm-ir");
-                    let output = filepath(self.output_dir, &out_file("llir"));
-                    fs::copy(tmp_file, output)?;
+                    let output = filepath(&case_dir, &out_file("llir"));
+                    let bytes = fs::copy(tmp_file, output)?;
+
+                    let size_file = filepath(&case_dir, &format!("{}.size", out_file("llir")));
+                    fs::write(size_file, format!("{} bytes\n", bytes))?;
+                }
+
+                // The crate dependency graph is captured by running
+                // `cargo tree --prefix none`, which writes to stdout; rustc is
+                // never involved. We copy that output into a file in the
+                // output dir.
+                Profiler::CrateGraph => {
+                    let graph_file = filepath(&case_dir, &out_file("depgraph"));
+
+                    fs::write(graph_file, output.stdout)?;
                 }
 
                 // `cargo llvm-lines` writes its output to stdout. We copy that
                 // output into a file in the output dir.
                 Profiler::LlvmLines => {
-                    let ll_file = filepath(self.output_dir, &out_file("ll"));
+                    let ll_file = filepath(&case_dir, &out_file("ll"));
 
                     fs::write(ll_file, output.stdout)?;
                 }
+
+                // A completely normal build; afterwards we walk the `deps`
+                // directory of the target dir and record the size of every
+                // compilation artifact, one `<name> <bytes>` line per file.
+                // For Opt builds we also record the stripped size of each
+                // artifact (as `<name> <bytes> <stripped-bytes>`), since that
+                // is what actually gets shipped.
+                Profiler::ArtifactSize => {
+                    let target_profile_dir = match data.profile {
+                        Profile::Opt => "release",
+                        _ => "debug",
+                    };
+                    let deps_dir = data
+                        .cwd
+                        .join("target")
+                        .join(target_profile_dir)
+                        .join("deps");
+                    let size_file = filepath(&case_dir, &out_file("artifact-size"));
+
+                    let mut sizes = Vec::new();
+                    for entry in fs::read_dir(&deps_dir)? {
+                        let entry = entry?;
+                        let path = entry.path();
+                        if !entry.file_type()?.is_file() {
+                            continue;
+                        }
+                        // Extensionless files are executables on unix.
+                        let is_artifact = match path.extension().and_then(|ext| ext.to_str()) {
+                            Some(ext) => {
+                                matches!(
+                                    ext,
+                                    "rlib" | "rmeta" | "a" | "so" | "dylib" | "dll" | "exe"
+                                )
+                            }
+                            None => true,
+                        };
+                        if !is_artifact {
+                            continue;
+                        }
+                        let name = entry.file_name().to_string_lossy().into_owned();
+                        let size = entry.metadata()?.len();
+                        let stripped = if data.profile == Profile::Opt {
+                            stripped_size(&path)
+                        } else {
+                            None
+                        };
+                        sizes.push((name, size, stripped));
+                    }
+                    sizes.sort();
+
+                    let mut file = io::BufWriter::new(File::create(size_file)?);
+                    for (name, size, stripped) in sizes {
+                        match stripped {
+                            Some(stripped) => writeln!(file, "{} {} {}", name, size, stripped)?,
+                            None => writeln!(file, "{} {}", name, size)?,
+                        }
+                    }
+                }
             }
             Ok(Retry::No)
         })
@@ -36,6 +36,10 @@ pub enum Bound {
     Commit(String),
     /// A date in time
     Date(NaiveDate),
+    /// An offset backwards from the latest master commit, written `@-n` (or
+    /// `HEAD~n`). Must be resolved against the commit list via [`Bound::resolve`]
+    /// before matching; an unresolved relative bound matches nothing.
+    Relative(i64),
     /// No bound
     #[default]
     None,
@@ -47,6 +51,7 @@ impl Bound {
         match self {
             Bound::Commit(sha) => commit.sha == **sha,
             Bound::Date(date) => commit.is_master() && commit.date.0.naive_utc().date() >= *date,
+            Bound::Relative(_) => false,
             Bound::None => {
                 let last_month = chrono::Utc::now().date_naive() - chrono::Duration::days(30);
                 commit.is_master() && last_month <= commit.date.0.naive_utc().date()
@@ -59,9 +64,33 @@ impl Bound {
         match self {
             Bound::Commit(sha) => commit.sha == **sha,
             Bound::Date(date) => commit.is_master() && commit.date.0.date_naive() <= *date,
+            Bound::Relative(_) => false,
             Bound::None => commit.is_master(),
         }
     }
+
+    /// Resolves a relative bound into a concrete commit bound using the given
+    /// commit list (ordered oldest to newest): `@-0` is the latest master
+    /// commit, `@-1` the one before it, and so on. Other bounds are returned
+    /// unchanged. An offset reaching past the start of the list stays
+    /// unresolved (and thus matches nothing).
+    pub fn resolve(self, commits: &[Commit]) -> Bound {
+        match self {
+            Bound::Relative(offset) => {
+                let masters = commits
+                    .iter()
+                    .filter(|commit| commit.is_master())
+                    .collect::<Vec<_>>();
+                masters
+                    .len()
+                    .checked_sub(1 + offset.unsigned_abs() as usize)
+                    .and_then(|idx| masters.get(idx).copied())
+                    .map(|commit| Bound::Commit(commit.sha.clone()))
+                    .unwrap_or(Bound::Relative(offset))
+            }
+            other => other,
+        }
+    }
 }
 
 impl serde::Serialize for Bound {
@@ -72,6 +101,7 @@ impl serde::Serialize for Bound {
         let s = match *self {
             Bound::Commit(ref s) => s.clone(),
             Bound::Date(ref date) => date.format("%Y-%m-%d").to_string(),
+            Bound::Relative(offset) => format!("@-{}", offset.unsigned_abs()),
             Bound::None => String::new(),
         };
         serializer.serialize_str(&s)
@@ -96,6 +126,16 @@ impl<'de> Deserialize<'de> for Bound {
                     return Ok(Bound::None);
                 }
 
+                if let Some(offset) = value
+                    .strip_prefix("@-")
+                    .or_else(|| value.strip_prefix("HEAD~"))
+                {
+                    return offset
+                        .parse::<i64>()
+                        .map(|offset| Bound::Relative(offset.abs()))
+                        .map_err(|_| E::custom(format!("invalid relative bound `{}`", value)));
+                }
+
                 let bound = value
                     .parse::<chrono::NaiveDate>()
                     .map(Bound::Date)
@@ -104,7 +144,10 @@ impl<'de> Deserialize<'de> for Bound {
             }
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                f.write_str("either a YYYY-mm-dd date or a collection ID (usually commit hash)")
+                f.write_str(
+                    "either a YYYY-mm-dd date, a collection ID (usually commit hash), \
+                     or a relative offset like `@-1`",
+                )
             }
         }
 
@@ -226,6 +269,38 @@ pub fn command_output(cmd: &mut Command) -> anyhow::Result<process::Output> {
     Ok(output)
 }
 
+/// Like [`command_output`], but re-runs the command on failure, up to
+/// `attempts` times in total, with a short delay in between. The error of the
+/// last attempt (including its captured stderr/stdout) is returned if all
+/// attempts fail. Since `Command` is not `Clone`, the command is rebuilt by
+/// the given closure for each attempt. Useful for invocations that can fail
+/// transiently, e.g. anything touching the network.
+pub fn command_output_retrying(
+    make_cmd: impl Fn() -> Command,
+    attempts: usize,
+) -> anyhow::Result<process::Output> {
+    assert!(attempts > 0);
+    let mut last_error = None;
+    for attempt in 0..attempts {
+        if attempt > 0 {
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        match command_output(&mut make_cmd()) {
+            Ok(output) => return Ok(output),
+            Err(error) => {
+                log::debug!(
+                    "command failed (attempt {}/{}): {:?}",
+                    attempt + 1,
+                    attempts,
+                    error
+                );
+                last_error = Some(error);
+            }
+        }
+    }
+    Err(last_error.unwrap())
+}
+
 pub async fn async_command_output(
     mut cmd: tokio::process::Command,
 ) -> anyhow::Result<process::Output> {
@@ -252,6 +327,72 @@ pub async fn async_command_output(
     Ok(output)
 }
 
+/// Error returned by [`async_command_output_with_timeout`] when the command
+/// did not finish before its deadline.
+#[derive(thiserror::Error, Debug)]
+#[error("command did not finish in {}s: {command}", .timeout.as_secs())]
+pub struct CommandTimedOut {
+    pub timeout: std::time::Duration,
+    pub command: String,
+}
+
+/// Like [`async_command_output`], but kills the child and returns a
+/// [`CommandTimedOut`] error if it does not finish within `timeout`. This
+/// guards against hung builds (e.g. a build script waiting on the network)
+/// blocking the collector forever.
+pub async fn async_command_output_with_timeout(
+    mut cmd: tokio::process::Command,
+    timeout: std::time::Duration,
+) -> anyhow::Result<process::Output> {
+    use anyhow::Context;
+
+    let start = Instant::now();
+    let child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // Dropping the wait future on timeout must not leave the child
+        // running.
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to spawn process for cmd: {:?}", cmd))?;
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => output?,
+        Err(_) => {
+            return Err(CommandTimedOut {
+                timeout,
+                command: format!("{cmd:?}"),
+            }
+            .into())
+        }
+    };
+    log::trace!("command {cmd:?} took {} ms", start.elapsed().as_millis());
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "expected success, got {}\n\nstderr={}\n\n stdout={}\n",
+            output.status,
+            String::from_utf8_lossy(&output.stderr),
+            String::from_utf8_lossy(&output.stdout)
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Set when a clean shutdown has been requested (e.g. by Ctrl-C). Checked
+/// between benchmarks and between profile/backend configurations; results
+/// that have already been recorded are kept, but the configuration that is
+/// currently being measured is not partially saved.
+static STOP_REQUESTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+pub fn request_stop() {
+    STOP_REQUESTED.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+pub fn stop_requested() -> bool {
+    STOP_REQUESTED.load(std::sync::atomic::Ordering::SeqCst)
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct MasterCommit {
     pub sha: String,
@@ -377,3 +518,66 @@ impl CollectorCtx {
 pub fn runtime_group_step_name(benchmark_name: &str) -> String {
     format!("runtime:{}", benchmark_name)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{command_output_retrying, Bound};
+
+    #[test]
+    fn bound_parsing() {
+        let parse = |s: &str| serde_json::from_str::<Bound>(&format!("\"{}\"", s)).unwrap();
+        assert_eq!(parse("@-5"), Bound::Relative(5));
+        assert_eq!(parse("HEAD~2"), Bound::Relative(2));
+        assert_eq!(
+            parse("2017-05-12"),
+            Bound::Date(chrono::NaiveDate::from_ymd_opt(2017, 5, 12).unwrap())
+        );
+        assert_eq!(parse("abcdef1234"), Bound::Commit("abcdef1234".into()));
+        assert_eq!(parse(""), Bound::None);
+    }
+
+    #[test]
+    fn retrying_succeeds_on_second_attempt() {
+        // The first run creates a marker file and fails; the second run sees
+        // the marker and succeeds.
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("marker");
+        let output = command_output_retrying(
+            || {
+                let mut cmd = std::process::Command::new("sh");
+                cmd.arg("-c")
+                    .arg(format!("test -f {0} || {{ touch {0}; exit 1; }}", "marker"))
+                    .current_dir(dir.path());
+                cmd
+            },
+            2,
+        )
+        .expect("second attempt should succeed");
+        assert!(output.status.success());
+        assert!(marker.is_file());
+    }
+
+    #[test]
+    fn retrying_reports_last_error() {
+        let err = command_output_retrying(
+            || {
+                let mut cmd = std::process::Command::new("sh");
+                cmd.arg("-c").arg("exit 3");
+                cmd
+            },
+            2,
+        )
+        .expect_err("command always fails");
+        assert!(err.to_string().contains("expected success"));
+    }
+
+    #[test]
+    fn relative_bound_round_trips() {
+        let json = serde_json::to_string(&Bound::Relative(5)).unwrap();
+        assert_eq!(json, "\"@-5\"");
+        assert_eq!(
+            serde_json::from_str::<Bound>(&json).unwrap(),
+            Bound::Relative(5)
+        );
+    }
+}
@@ -11,6 +11,24 @@ use std::{fmt, str};
 use tar::Archive;
 use xz2::bufread::XzDecoder;
 
+/// How many unpacked sysroots are kept in the on-disk cache. Once the limit
+/// is exceeded, the least recently installed sysroots are evicted.
+const MAX_CACHED_SYSROOTS: usize = 5;
+
+static SYSROOT_CACHE_DISABLED: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+/// Disables reuse of previously unpacked sysroots. Every `Sysroot::install`
+/// will extract from scratch and the sysroot will be removed again when it is
+/// dropped, as if the cache did not exist.
+pub fn disable_sysroot_cache() {
+    SYSROOT_CACHE_DISABLED.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn sysroot_cache_enabled() -> bool {
+    !SYSROOT_CACHE_DISABLED.load(std::sync::atomic::Ordering::SeqCst)
+}
+
 /// Sysroot downloaded from CI.
 pub struct Sysroot {
     pub sha: String,
@@ -31,6 +49,27 @@ impl Sysroot {
             triple: triple.to_owned(),
         };
 
+        // Reuse an already unpacked sysroot for this (sha, triple) if there is
+        // one. The marker file is only written after all components were
+        // extracted successfully, so an interrupted install cannot poison the
+        // cache.
+        if sysroot_cache_enabled() && download.complete_marker().is_file() {
+            match download.clone().into_sysroot() {
+                Ok(sysroot) => {
+                    log::info!("reusing cached sysroot for {}", sysroot.sha);
+                    return Ok(sysroot);
+                }
+                Err(err) => {
+                    log::warn!(
+                        "cached sysroot for {} is unusable ({:?}), reinstalling",
+                        download.rust_sha,
+                        err
+                    );
+                }
+            }
+        }
+
+        let start = std::time::Instant::now();
         download.get_and_extract(Component::Rustc)?;
         download.get_and_extract(Component::Std)?;
         download.get_and_extract(Component::Cargo)?;
@@ -38,6 +77,18 @@ impl Sysroot {
         if backends.contains(&CodegenBackend::Cranelift) {
             download.get_and_extract(Component::Cranelift)?;
         }
+        log::debug!(
+            "installed sysroot for {} in {:.1}s",
+            download.rust_sha,
+            start.elapsed().as_secs_f64()
+        );
+
+        if sysroot_cache_enabled() {
+            if let Err(err) = fs::write(download.complete_marker(), "") {
+                log::warn!("failed to mark sysroot as complete: {:?}", err);
+            }
+            evict_old_sysroots(&download.directory, &download.rust_sha);
+        }
 
         let sysroot = download.into_sysroot()?;
 
@@ -49,11 +100,44 @@ impl Sysroot {
     }
 }
 
+/// Removes the least recently installed sysroots so that at most
+/// `MAX_CACHED_SYSROOTS` (including the one just installed) remain on disk.
+fn evict_old_sysroots(cache_dir: &Path, current_sha: &str) {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return;
+    };
+    let mut sysroots: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            if !path.is_dir() || entry.file_name() == OsStr::new(current_sha) {
+                return None;
+            }
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, path))
+        })
+        .collect();
+    sysroots.sort_by_key(|(modified, _)| *modified);
+    let keep = MAX_CACHED_SYSROOTS.saturating_sub(1);
+    let excess = sysroots.len().saturating_sub(keep);
+    for (_, path) in sysroots.into_iter().take(excess) {
+        log::info!("evicting cached sysroot {:?}", path);
+        if let Err(err) = fs::remove_dir_all(&path) {
+            log::warn!("failed to evict {:?}: {:?}", path, err);
+        }
+    }
+}
+
 impl Drop for Sysroot {
     fn drop(&mut self) {
         if self.preserve {
             return;
         }
+        if sysroot_cache_enabled() {
+            // Keep the unpacked sysroot around for reuse; eviction in
+            // `Sysroot::install` bounds the total disk usage.
+            return;
+        }
         fs::remove_dir_all(format!("cache/{}", self.sha)).unwrap_or_else(|err| {
             log::info!(
                 "failed to remove {:?}, please do so manually: {:?}",
@@ -113,6 +197,14 @@ impl Component {
 }
 
 impl SysrootDownload {
+    /// Path of the marker file recording that all components for this
+    /// (sha, triple) were extracted successfully.
+    fn complete_marker(&self) -> PathBuf {
+        self.directory
+            .join(&self.rust_sha)
+            .join(format!(".complete-{}", self.triple))
+    }
+
     fn into_sysroot(self) -> anyhow::Result<Sysroot> {
         let sysroot_bin_dir = self.directory.join(&self.rust_sha).join("bin");
         let sysroot_bin = |name| {
@@ -572,6 +664,17 @@ pub fn create_toolchain_from_published_version(
     debug!("Found clippy: {}", clippy.display());
     debug!("Found cargo: {}", cargo.display());
 
+    // Channel names like `nightly`, `nightly-2021-05-01` or `beta` would make
+    // the recorded artifact id ambiguous, so resolve the version that
+    // actually got installed and use that as the id instead.
+    let id = if toolchain.starts_with("nightly") || toolchain.starts_with("beta") {
+        let version = resolve_rustc_version(&rustc)?;
+        eprintln!("Resolved toolchain `{toolchain}` to `{version}`");
+        version
+    } else {
+        toolchain.to_string()
+    };
+
     let lib_dir = get_lib_dir_from_rustc(&rustc)?;
 
     let components = ToolchainComponents::from_binaries_and_libdir(
@@ -584,11 +687,41 @@ pub fn create_toolchain_from_published_version(
 
     Ok(Toolchain {
         components,
-        id: toolchain.to_string(),
+        id,
         triple: target_triple.to_string(),
     })
 }
 
+/// Resolves the version of the given rustc binary via `rustc -vV`, e.g.
+/// `1.54.0-nightly (2021-05-01)` for channel toolchains.
+fn resolve_rustc_version(rustc: &Path) -> anyhow::Result<String> {
+    let output = Command::new(rustc)
+        .arg("-vV")
+        .output()
+        .context("rustc -vV")?;
+    if !output.status.success() {
+        anyhow::bail!("rustc -vV failed, exit status: {}", output.status);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let field = |name: &str| {
+        stdout
+            .lines()
+            .find_map(|line| {
+                line.strip_prefix(name)
+                    .map(|value| value.trim().to_string())
+            })
+            .ok_or_else(|| anyhow::anyhow!("`{name}` missing from rustc -vV output"))
+    };
+    let release = field("release:")?;
+    // Stable releases are already unambiguous; pre-release channels need the
+    // commit date to tell individual nightlies/betas apart.
+    if release.contains("nightly") || release.contains("beta") {
+        Ok(format!("{} ({})", release, field("commit-date:")?))
+    } else {
+        Ok(release)
+    }
+}
+
 fn get_lib_dir_from_rustc(rustc: &Path) -> anyhow::Result<PathBuf> {
     let output = Command::new(rustc).arg("--print").arg("sysroot").output()?;
     if !output.status.success() {
@@ -1,5 +1,5 @@
 use chrono::offset::TimeZone;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Utc};
 use hashbrown::HashMap;
 use intern::intern;
 use serde::{Deserialize, Serialize};
@@ -63,6 +63,24 @@ impl Date {
     pub fn empty() -> Date {
         Date::ymd_hms(2000, 1, 1, 1, 1, 1)
     }
+
+    /// The Sunday starting the week this date falls in, at midnight UTC.
+    pub fn start_of_week(&self) -> Date {
+        let days_from_sunday = self.0.weekday().num_days_from_sunday();
+        let sunday = self.0.date_naive() - chrono::Duration::days(days_from_sunday as i64);
+        Date(sunday.and_hms_opt(0, 0, 0).unwrap().and_utc())
+    }
+
+    /// The Saturday ending the week this date falls in, at midnight UTC.
+    pub fn end_of_week(&self) -> Date {
+        Date(self.start_of_week().0 + chrono::Duration::days(6))
+    }
+
+    /// The inclusive (Sunday, Saturday) pair delimiting the week this date
+    /// falls in.
+    pub fn week_range(&self) -> (Date, Date) {
+        (self.start_of_week(), self.end_of_week())
+    }
 }
 
 impl fmt::Display for Date {
@@ -157,6 +175,16 @@ pub struct Commit {
     pub sha: String,
     pub date: Date,
     pub r#type: CommitType,
+    /// The commit subject line, when known. Population is best-effort: the
+    /// database does not store it, so it is only present on commits built
+    /// from sources that carry it. Identity (`Eq`/`Hash`/`Ord`) is still
+    /// purely sha/date based.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// The pull request this commit merged, when known (e.g. from the
+    /// master-commit list). Best-effort, like `message`.
+    #[serde(default)]
+    pub pr: Option<u32>,
 }
 
 impl Commit {
@@ -166,6 +194,15 @@ impl Commit {
     pub fn is_master(&self) -> bool {
         matches!(self.r#type, CommitType::Master)
     }
+    /// An abbreviated sha for log output: the first 9 characters, or the
+    /// whole id if it is shorter. Safe on the synthetic ids used by local
+    /// benchmarking, which need not be 40 hex chars (or ASCII at all).
+    pub fn short_sha(&self) -> &str {
+        match self.sha.char_indices().nth(9) {
+            Some((idx, _)) => &self.sha[..idx],
+            None => &self.sha,
+        }
+    }
 }
 
 impl hash::Hash for Commit {
@@ -294,6 +331,13 @@ impl fmt::Display for Scenario {
 }
 
 impl Scenario {
+    /// Whether this scenario was measured with incremental compilation
+    /// enabled. Prefer this over matching on the variants: the `Empty` vs
+    /// `IncrementalEmpty` distinction is easy to get wrong.
+    pub fn is_incr(&self) -> bool {
+        !matches!(self, Scenario::Empty)
+    }
+
     pub fn to_id(&self) -> String {
         match self {
             Scenario::Empty => "full".to_string(),
@@ -752,3 +796,44 @@ pub struct ArtifactCollection {
     pub duration: Duration,
     pub end_time: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Commit, CommitType, Date};
+
+    #[test]
+    fn short_sha_handles_synthetic_ids() {
+        let commit = |sha: &str| Commit {
+            sha: sha.to_string(),
+            date: Date::ymd_hms(2021, 9, 5, 0, 0, 0),
+            r#type: CommitType::Master,
+            message: None,
+            pr: None,
+        };
+
+        assert_eq!(
+            commit("8769f4ef2fe1f822b2ebb9e03880a82509775246").short_sha(),
+            "8769f4ef2"
+        );
+        // Synthetic ids from local benchmarking may be arbitrarily short.
+        assert_eq!(commit("<none>").short_sha(), "<none>");
+        assert_eq!(commit("").short_sha(), "");
+    }
+
+    #[test]
+    fn week_boundaries() {
+        // 2021-09-05 was a Sunday, 2021-09-11 the following Saturday.
+        let sunday = Date::ymd_hms(2021, 9, 5, 0, 0, 0);
+        let saturday = Date::ymd_hms(2021, 9, 11, 23, 59, 59);
+        let midweek = Date::ymd_hms(2021, 9, 8, 12, 30, 0);
+
+        for date in [sunday, midweek, saturday] {
+            assert_eq!(date.start_of_week(), Date::ymd_hms(2021, 9, 5, 0, 0, 0));
+            assert_eq!(date.end_of_week(), Date::ymd_hms(2021, 9, 11, 0, 0, 0));
+            assert_eq!(
+                date.week_range(),
+                (date.start_of_week(), date.end_of_week())
+            );
+        }
+    }
+}
@@ -4,7 +4,7 @@ use serde::{de::IntoDeserializer, Deserialize, Serialize};
 
 /// This enum contains all "known" metrics coming from rustc or profiling tools that we know
 /// (and care) about.
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Metric {
     #[serde(rename = "context-switches")]
     ContextSwitches,
@@ -22,6 +22,10 @@ pub enum Metric {
     FaultsUser,
     #[serde(rename = "instructions:u")]
     InstructionsUser,
+    /// Peak resident set size, in *kilobytes*. This is the unit reported by
+    /// `getrusage` on Linux (`ru_maxrss`); macOS reports bytes and Windows
+    /// reports `PeakWorkingSetSize` in bytes, and `rustc-fake` normalizes
+    /// both to kilobytes before the value gets here.
     #[serde(rename = "max-rss")]
     MaxRSS,
     #[serde(rename = "task-clock")]
@@ -77,6 +81,27 @@ impl FromStr for Metric {
     }
 }
 
+/// The unit in which a [`Metric`] is measured.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Unit {
+    Seconds,
+    Count,
+    Bytes,
+    Kilobytes,
+}
+
+impl Unit {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Unit::Seconds => "seconds",
+            Unit::Count => "count",
+            Unit::Bytes => "bytes",
+            Unit::Kilobytes => "kilobytes",
+        }
+    }
+}
+
 impl Metric {
     pub fn as_str(&self) -> &str {
         match self {
@@ -110,4 +135,39 @@ impl Metric {
             Metric::DocFilesCount => "size:doc_files_count",
         }
     }
+
+    /// The unit this metric is measured in. Frontends should use this instead
+    /// of guessing from the metric name.
+    pub fn unit(&self) -> Unit {
+        match self {
+            Metric::CpuClock
+            | Metric::CpuClockUser
+            | Metric::TaskClock
+            | Metric::TaskClockUser
+            | Metric::WallTime => Unit::Seconds,
+            Metric::ContextSwitches
+            | Metric::Cycles
+            | Metric::CyclesUser
+            | Metric::Faults
+            | Metric::FaultsUser
+            | Metric::InstructionsUser
+            | Metric::BranchMisses
+            | Metric::CacheMisses
+            | Metric::CodegenUnitSize
+            | Metric::CodegenUnitLlvmIrCount
+            | Metric::DocFilesCount => Unit::Count,
+            Metric::MaxRSS => Unit::Kilobytes,
+            Metric::DepGraphSize
+            | Metric::LinkedArtifactSize
+            | Metric::ObjectFileSize
+            | Metric::QueryCacheSize
+            | Metric::WorkProductIndexSize
+            | Metric::CrateMetadataSize
+            | Metric::DwoFileSize
+            | Metric::AssemblyFileSize
+            | Metric::LlvmBitcodeSize
+            | Metric::LlvmIrSize
+            | Metric::DocByteSize => Unit::Bytes,
+        }
+    }
 }
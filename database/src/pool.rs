@@ -69,6 +69,9 @@ pub trait Connection: Send + Sync {
         scenario: Scenario,
     );
     async fn record_error(&self, artifact: ArtifactIdNumber, krate: &str, error: &str);
+    /// Removes a previously recorded error for the given benchmark, e.g.
+    /// before re-running a benchmark that errored in an earlier run.
+    async fn clear_error(&self, artifact: ArtifactIdNumber, krate: &str);
     async fn record_rustc_crate(
         &self,
         collection: CollectionId,
@@ -84,6 +87,17 @@ pub trait Connection: Send + Sync {
     /// Returns the sizes of individual components of a single artifact.
     async fn get_artifact_size(&self, aid: ArtifactIdNumber) -> HashMap<String, u64>;
 
+    /// Records a best-effort, JSON-serialized fingerprint of the machine
+    /// environment (hostname, CPU model, kernel, perf version, ...) that an
+    /// artifact was benchmarked on.
+    async fn record_environment(&self, artifact: ArtifactIdNumber, fingerprint: &str);
+
+    /// Returns the environment fingerprint recorded for an artifact, if any.
+    async fn get_environment(&self, artifact: ArtifactIdNumber) -> Option<String>;
+
+    /// Returns every recorded environment fingerprint, across all artifacts.
+    async fn get_environments(&self) -> Vec<String>;
+
     /// Returns vector of bootstrap build times for the given artifacts. The kth
     /// element is the minimum build time for the kth artifact in `aids`, across
     /// all collections for the artifact, or none if there is no bootstrap data
@@ -175,6 +189,15 @@ pub trait Connection: Send + Sync {
 
     /// Removes all data associated with the given artifact.
     async fn purge_artifact(&self, aid: &ArtifactId);
+
+    /// The number of artifacts and stored compile-time result rows that
+    /// reference the given benchmark, for reporting the scope of a purge
+    /// before (or after) doing it.
+    async fn compile_benchmark_scope(&self, benchmark: &str) -> (u32, u32);
+
+    /// Removes a compile benchmark; its series and results are deleted with
+    /// it via cascade.
+    async fn purge_compile_benchmark(&self, benchmark: &str);
 }
 
 #[async_trait::async_trait]
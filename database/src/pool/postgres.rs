@@ -279,6 +279,18 @@ static MIGRATIONS: &[&str] = &[
     alter table pstat_series add constraint test_case UNIQUE(crate, profile, scenario, backend, metric);
     "#,
     r#"alter table pull_request_build add column backends text;"#,
+    // Best-effort fingerprint (JSON) of the machine an artifact was
+    // benchmarked on, for detecting cross-environment comparisons.
+    r#"
+    create table environment(
+        aid integer primary key references artifact(id) on delete cascade on update cascade,
+        fingerprint text not null
+    );
+"#,
+    // When each collection (one per measured iteration) was recorded, so that
+    // outlier measurements can be correlated with external machine monitoring
+    // (load, temperature, ...). Null for historical rows.
+    r#"alter table collection add column date_recorded timestamptz;"#,
 ];
 
 #[async_trait::async_trait]
@@ -468,7 +480,7 @@ impl PostgresConnection {
                 get_error: conn.prepare("select benchmark, error from error where aid = $1").await.unwrap(),
                 insert_pstat_series: conn.prepare("insert into pstat_series (crate, profile, scenario, backend, metric) VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING RETURNING id").await.unwrap(),
                 select_pstat_series: conn.prepare("select id from pstat_series where crate = $1 and profile = $2 and scenario = $3 and backend = $4 and metric = $5").await.unwrap(),
-                collection_id: conn.prepare("insert into collection (perf_commit) VALUES ($1) returning id").await.unwrap(),
+                collection_id: conn.prepare("insert into collection (perf_commit, date_recorded) VALUES ($1, CURRENT_TIMESTAMP) returning id").await.unwrap(),
                 record_duration: conn.prepare("
                     insert into artifact_collection_duration (
                         aid,
@@ -596,7 +608,9 @@ where
                                     None => Date(Utc.with_ymd_and_hms(2001, 1, 1, 0, 0, 0).unwrap()),
                                 }
                             },
-                            r#type: CommitType::from_str(&row.get::<_, String>(3)).unwrap()
+                            r#type: CommitType::from_str(&row.get::<_, String>(3)).unwrap(),
+                            message: None,
+                            pr: None,
                         },
                     )
                 })
@@ -948,6 +962,38 @@ where
             .unwrap();
     }
 
+    async fn record_environment(&self, artifact: ArtifactIdNumber, fingerprint: &str) {
+        self.conn()
+            .execute(
+                "insert into environment (aid, fingerprint) values ($1, $2) \
+                 on conflict (aid) do update set fingerprint = excluded.fingerprint",
+                &[&(artifact.0 as i32), &fingerprint],
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn get_environment(&self, artifact: ArtifactIdNumber) -> Option<String> {
+        self.conn()
+            .query_opt(
+                "select fingerprint from environment where aid = $1",
+                &[&(artifact.0 as i32)],
+            )
+            .await
+            .unwrap()
+            .map(|row| row.get(0))
+    }
+
+    async fn get_environments(&self) -> Vec<String> {
+        self.conn()
+            .query("select fingerprint from environment", &[])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|row| row.get(0))
+            .collect()
+    }
+
     async fn get_artifact_size(&self, aid: ArtifactIdNumber) -> HashMap<String, u64> {
         let rows = self
             .conn()
@@ -1000,6 +1046,16 @@ where
             .unwrap();
     }
 
+    async fn clear_error(&self, artifact: ArtifactIdNumber, krate: &str) {
+        self.conn()
+            .execute(
+                "delete from error where benchmark = $1 and aid = $2",
+                &[&krate, &(artifact.0 as i32)],
+            )
+            .await
+            .unwrap();
+    }
+
     async fn record_compile_benchmark(
         &self,
         benchmark: &str,
@@ -1140,6 +1196,8 @@ where
                         .map(Date)
                         .unwrap_or_else(|| Date::ymd_hms(2001, 1, 1, 0, 0, 0)),
                     r#type: CommitType::from_str(&ty).unwrap(),
+                    message: None,
+                    pr: None,
                 }),
                 "release" => ArtifactId::Tag(row.get(0)),
                 _ => {
@@ -1356,6 +1414,29 @@ where
             .await
             .unwrap();
     }
+
+    async fn compile_benchmark_scope(&self, benchmark: &str) -> (u32, u32) {
+        let row = self
+            .conn()
+            .query_one(
+                "select count(distinct pstat.aid), count(*) from pstat
+                    join pstat_series on pstat.series = pstat_series.id
+                    where pstat_series.crate = $1",
+                &[&benchmark],
+            )
+            .await
+            .unwrap();
+        (row.get::<_, i64>(0) as u32, row.get::<_, i64>(1) as u32)
+    }
+
+    async fn purge_compile_benchmark(&self, benchmark: &str) {
+        // The series and result rows referencing the benchmark go with it
+        // thanks to ON DELETE CASCADE.
+        self.conn()
+            .execute("delete from benchmark where name = $1", &[&benchmark])
+            .await
+            .unwrap();
+    }
 }
 
 fn parse_artifact_id(ty: &str, sha: &str, date: Option<DateTime<Utc>>) -> ArtifactId {
@@ -1364,6 +1445,8 @@ fn parse_artifact_id(ty: &str, sha: &str, date: Option<DateTime<Utc>>) -> Artifa
             sha: sha.to_owned(),
             date: Date(date.expect("date present for master commits")),
             r#type: CommitType::Master,
+            message: None,
+            pr: None,
         }),
         "try" => ArtifactId::Commit(Commit {
             sha: sha.to_owned(),
@@ -1371,6 +1454,8 @@ fn parse_artifact_id(ty: &str, sha: &str, date: Option<DateTime<Utc>>) -> Artifa
                 .map(Date)
                 .unwrap_or_else(|| Date::ymd_hms(2000, 1, 1, 0, 0, 0)),
             r#type: CommitType::Try,
+            message: None,
+            pr: None,
         }),
         "release" => ArtifactId::Tag(sha.to_owned()),
         _ => panic!("unknown artifact type: {:?}", ty),
@@ -386,6 +386,20 @@ static MIGRATIONS: &[Migration] = &[
     "#,
     ),
     Migration::new("alter table pull_request_build add column backends text"),
+    // Best-effort fingerprint (JSON) of the machine an artifact was
+    // benchmarked on, for detecting cross-environment comparisons.
+    Migration::new(
+        r#"
+        create table environment(
+            aid integer primary key references artifact(id) on delete cascade on update cascade,
+            fingerprint text not null
+        );
+    "#,
+    ),
+    // When each collection (one per measured iteration) was recorded, so
+    // that outlier measurements can be correlated with external machine
+    // monitoring (load, temperature, ...). Null for historical rows.
+    Migration::new("alter table collection add column date_recorded timestamp"),
 ];
 
 #[async_trait::async_trait]
@@ -480,6 +494,8 @@ impl Connection for SqliteConnection {
                             }
                         },
                         r#type: CommitType::from_str(&row.get::<_, String>(3)?).unwrap(),
+                        message: None,
+                        pr: None,
                     },
                 ))
             })
@@ -614,7 +630,7 @@ impl Connection for SqliteConnection {
     async fn collection_id(&self, version: &str) -> CollectionId {
         let raw = self.raw_ref();
         raw.execute(
-            "insert into collection (perf_commit) values (?)",
+            "insert into collection (perf_commit, date_recorded) values (?, strftime('%s','now'))",
             params![version],
         )
         .unwrap();
@@ -735,6 +751,15 @@ impl Connection for SqliteConnection {
             )
             .unwrap();
     }
+
+    async fn clear_error(&self, artifact: ArtifactIdNumber, krate: &str) {
+        self.raw_ref()
+            .execute(
+                "delete from error where benchmark = ? and aid = ?",
+                params![krate, &artifact.0],
+            )
+            .unwrap();
+    }
     async fn record_rustc_crate(
         &self,
         collection: CollectionId,
@@ -765,6 +790,36 @@ impl Connection for SqliteConnection {
             .unwrap();
     }
 
+    async fn record_environment(&self, artifact: ArtifactIdNumber, fingerprint: &str) {
+        self.raw_ref()
+            .execute(
+                "insert or replace into environment (aid, fingerprint) values (?, ?)",
+                params![&artifact.0, &fingerprint],
+            )
+            .unwrap();
+    }
+
+    async fn get_environment(&self, artifact: ArtifactIdNumber) -> Option<String> {
+        self.raw_ref()
+            .query_row(
+                "select fingerprint from environment where aid = ?",
+                params![&artifact.0],
+                |row| row.get(0),
+            )
+            .optional()
+            .unwrap()
+    }
+
+    async fn get_environments(&self) -> Vec<String> {
+        self.raw_ref()
+            .prepare("select fingerprint from environment")
+            .unwrap()
+            .query_map(params![], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
     async fn get_artifact_size(&self, aid: ArtifactIdNumber) -> HashMap<String, u64> {
         self.raw_ref()
             .prepare("select component, size from artifact_size where aid = ?")
@@ -1084,6 +1139,8 @@ impl Connection for SqliteConnection {
                         .map(Date)
                         .unwrap_or_else(|| Date::ymd_hms(2001, 1, 1, 0, 0, 0)),
                     r#type: CommitType::from_str(&ty).unwrap(),
+                    message: None,
+                    pr: None,
                 }),
                 "release" => ArtifactId::Tag(name),
                 _ => {
@@ -1227,6 +1284,26 @@ impl Connection for SqliteConnection {
             )
             .unwrap();
     }
+
+    async fn compile_benchmark_scope(&self, benchmark: &str) -> (u32, u32) {
+        self.raw_ref()
+            .query_row(
+                "select count(distinct pstat.aid), count(*) from pstat
+                    join pstat_series on pstat.series = pstat_series.id
+                    where pstat_series.crate = ?",
+                params![benchmark],
+                |row| Ok((row.get::<_, i64>(0)? as u32, row.get::<_, i64>(1)? as u32)),
+            )
+            .unwrap()
+    }
+
+    async fn purge_compile_benchmark(&self, benchmark: &str) {
+        // The series and result rows referencing the benchmark go with it
+        // thanks to ON DELETE CASCADE.
+        self.raw_ref()
+            .execute("delete from benchmark where name = ?", params![benchmark])
+            .unwrap();
+    }
 }
 
 fn parse_artifact_id(ty: &str, sha: &str, date: Option<i64>) -> ArtifactId {
@@ -1238,6 +1315,8 @@ fn parse_artifact_id(ty: &str, sha: &str, date: Option<i64>) -> ArtifactId {
                     .unwrap(),
             ),
             r#type: CommitType::Master,
+            message: None,
+            pr: None,
         }),
         "try" => ArtifactId::Commit(Commit {
             sha: sha.to_owned(),
@@ -1245,6 +1324,8 @@ fn parse_artifact_id(ty: &str, sha: &str, date: Option<i64>) -> ArtifactId {
                 .map(|d| Date(Utc.timestamp_opt(d, 0).unwrap()))
                 .unwrap_or_else(|| Date::ymd_hms(2000, 1, 1, 0, 0, 0)),
             r#type: CommitType::Try,
+            message: None,
+            pr: None,
         }),
         "release" => ArtifactId::Tag(sha.to_owned()),
         _ => panic!("unknown artifact type: {:?}", ty),
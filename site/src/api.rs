@@ -41,9 +41,45 @@ where
     deserializer.deserialize_str(CommaSeparatedVisitor(Default::default()))
 }
 
+// Deserializes an optional count that may arrive either as a JSON number or
+// as a string, which is how query-string parameters are represented.
+fn optional_usize<'de, D>(deserializer: D) -> Result<Option<usize>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct OptionalUsizeVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for OptionalUsizeVisitor {
+        type Value = Option<usize>;
+
+        fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+            formatter.write_str("an optional non-negative integer (or a string holding one)")
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D2: Deserializer<'de>>(self, d: D2) -> Result<Self::Value, D2::Error> {
+            d.deserialize_any(OptionalUsizeVisitor)
+        }
+
+        fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(Some(v as usize))
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            v.parse().map(Some).map_err(Error::custom)
+        }
+    }
+
+    deserializer.deserialize_option(OptionalUsizeVisitor)
+}
+
 pub mod info {
     use database::Date;
     use serde::Serialize;
+    use std::collections::BTreeMap;
 
     #[derive(Debug, Clone, PartialEq, Serialize)]
     pub struct Response {
@@ -53,8 +89,87 @@ pub mod info {
         /// Sorted list of known runtime metrics
         pub runtime_metrics: Vec<String>,
 
+        /// Maps known metrics to the unit they are measured in
+        /// ("seconds", "count", "bytes" or "kilobytes"), so that clients do
+        /// not have to guess from the metric name. Metrics the server does
+        /// not recognize are absent.
+        pub stat_units: BTreeMap<String, String>,
+
         /// Chronologically last loaded run date.
         pub as_of: Option<Date>,
+
+        /// Whether `as_of` lags behind the current time by more than the
+        /// configured staleness threshold (i.e. collection looks stalled).
+        /// Recomputed per request, even when the rest of the response is
+        /// served from cache.
+        pub is_stale: bool,
+
+        /// Sorted, deduplicated list of target triples with data, taken from
+        /// the environment fingerprints recorded per artifact. Empty when the
+        /// database predates fingerprints carrying the triple.
+        pub triples: Vec<String>,
+    }
+}
+
+pub mod index_dump {
+    use database::Commit;
+    use serde::Serialize;
+
+    /// One (benchmark, profile, scenario, backend) combination for which the
+    /// index has at least one measurement, with the metrics recorded for it.
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+    pub struct CompileTestCase {
+        pub benchmark: String,
+        pub profile: String,
+        pub scenario: String,
+        pub backend: String,
+        /// Sorted list of metrics present for this test case.
+        pub metrics: Vec<String>,
+    }
+
+    /// A dump of the whole index, intended for offline analysis tools that
+    /// would otherwise have to scrape many per-commit endpoints just to
+    /// discover what data exists.
+    ///
+    /// Note that the index records which test cases exist globally, not which
+    /// of them were measured for each individual commit; missing data points
+    /// still have to be discovered by querying the commit in question.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct Response {
+        /// All benchmarked commits, sorted chronologically.
+        pub commits: Vec<Commit>,
+
+        /// Non-commit artifacts (released toolchains), sorted by tag.
+        pub artifacts: Vec<String>,
+
+        /// All compile-time test cases present in the index, sorted.
+        pub compile_test_cases: Vec<CompileTestCase>,
+
+        /// Sorted list of runtime benchmarks present in the index.
+        pub runtime_benchmarks: Vec<String>,
+    }
+}
+
+pub mod errors {
+    use collector::Bound;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Request {
+        /// The commit (or date/relative bound) whose collection errors are
+        /// requested.
+        pub commit: Bound,
+    }
+
+    #[derive(Debug, PartialEq, Clone, Serialize)]
+    pub struct Response {
+        /// The artifact the bound resolved to.
+        pub artifact: String,
+
+        /// Maps each benchmark that errored during the collection of this
+        /// artifact to its (human-readable) error text.
+        pub errors: BTreeMap<String, String>,
     }
 }
 
@@ -115,6 +230,14 @@ pub mod graphs {
         pub benchmark: Option<String>,
         pub scenario: Option<String>,
         pub profile: Option<String>,
+
+        /// Upper bound on the number of returned points per series. When the
+        /// requested range contains more commits, they are bucketed and one
+        /// representative commit is kept per bucket (the first and last
+        /// commits are always preserved). When absent, every commit in the
+        /// range is returned.
+        #[serde(default, deserialize_with = "crate::api::optional_usize")]
+        pub max_points: Option<usize>,
     }
 
     #[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
@@ -132,6 +255,11 @@ pub mod graphs {
     pub struct Series {
         // y-values
         pub points: Vec<f32>,
+        /// Net change from the first to the last raw value of the series, in
+        /// percent. Always computed from the raw values, independently of the
+        /// requested `GraphKind`, so consumers get a ready-made "how much did
+        /// this move over the range" number. Zero for empty series.
+        pub delta_percent: f32,
         // The index of interpolated coordinates
         pub interpolated_indices: HashSet<u16>,
     }
@@ -180,6 +308,14 @@ pub mod detail_sections {
         pub benchmark: String,
         pub scenario: String,
         pub profile: String,
+
+        /// When present, additionally compute the sections for (up to) this
+        /// many master commits sampled evenly from the range, producing a
+        /// per-section time series that shows *which* part of compilation
+        /// regressed. Each point requires the commit's self-profile data, so
+        /// the server caps this at a small maximum.
+        #[serde(default, deserialize_with = "crate::api::optional_usize")]
+        pub points: Option<usize>,
     }
 
     #[derive(Default, Debug, Clone, Serialize)]
@@ -197,10 +333,23 @@ pub mod detail_sections {
         pub sections: Vec<CompilationSection>,
     }
 
+    /// The sections of one sampled commit, for the `points` time series.
+    #[derive(Debug, Serialize)]
+    pub struct CommitSections {
+        /// The commit's sha.
+        pub commit: String,
+        /// Seconds since the epoch of the commit date, for the x-axis.
+        pub date: i64,
+        pub sections: CompilationSections,
+    }
+
     #[derive(Debug, Serialize)]
     pub struct Response {
         pub before: Option<CompilationSections>,
         pub after: Option<CompilationSections>,
+        /// One entry per sampled commit with self-profile data, in
+        /// chronological order; only present when `points` was requested.
+        pub series: Option<Vec<CommitSections>>,
     }
 }
 
@@ -261,6 +410,11 @@ pub mod comparison {
     pub struct Request {
         pub start: Bound,
         pub end: Bound,
+        /// If set, `start` is ignored and replaced by the commit this many
+        /// entries before `end` in the commit ordering ("current vs N builds
+        /// ago").
+        #[serde(default)]
+        pub offset: Option<usize>,
         pub stat: Metric,
     }
 
@@ -343,6 +497,49 @@ pub mod comparison {
     }
 }
 
+pub mod compare {
+    use collector::Bound;
+    use database::metric::Metric;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Clone, Deserialize)]
+    pub struct Request {
+        pub start: Bound,
+        pub end: Bound,
+        pub stat: Metric,
+    }
+
+    /// The delta of a single compile test case between the two artifacts.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct BenchmarkDelta {
+        pub benchmark: String,
+        pub profile: String,
+        pub scenario: String,
+        pub backend: String,
+        /// Value at `start`; `null` if only measured at `end`.
+        pub a: Option<f64>,
+        /// Value at `end`; `null` if only measured at `start`.
+        pub b: Option<f64>,
+        /// Absolute delta `b - a`; `null` unless measured on both sides.
+        pub delta: Option<f64>,
+        /// Relative delta in percent; `null` unless measured on both sides.
+        pub percent: Option<f64>,
+        /// Whether the relative delta exceeds the significance threshold.
+        pub significant: bool,
+    }
+
+    #[derive(Debug, Clone, Serialize)]
+    pub struct Response {
+        pub a: String,
+        pub b: String,
+        /// How many deltas exceed the significance threshold, so clients get
+        /// a cheap "something changed here" marker without thresholding
+        /// themselves.
+        pub num_significant: usize,
+        pub deltas: Vec<BenchmarkDelta>,
+    }
+}
+
 pub mod status {
     use crate::load::MissingReason;
     use database::ArtifactId;
@@ -610,3 +807,25 @@ pub mod triage {
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct Response(pub String);
 }
+
+#[cfg(test)]
+mod tests {
+    #[derive(serde::Deserialize)]
+    struct Holder {
+        #[serde(default, deserialize_with = "super::optional_usize")]
+        points: Option<usize>,
+    }
+
+    // Query-string parameters arrive as strings, JSON bodies as numbers;
+    // both must work.
+    #[test]
+    fn optional_usize_accepts_numbers_and_strings() {
+        let h: Holder = serde_json::from_str(r#"{"points": 7}"#).unwrap();
+        assert_eq!(h.points, Some(7));
+        let h: Holder = serde_json::from_str(r#"{"points": "12"}"#).unwrap();
+        assert_eq!(h.points, Some(12));
+        let h: Holder = serde_json::from_str("{}").unwrap();
+        assert_eq!(h.points, None);
+        assert!(serde_json::from_str::<Holder>(r#"{"points": "x"}"#).is_err());
+    }
+}
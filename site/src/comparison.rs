@@ -132,11 +132,19 @@ pub async fn handle_compare(
     let master_commits = &ctxt.get_master_commits().commits;
 
     let end = body.end;
-    let comparison =
-        compare_given_commits(body.start, end.clone(), body.stat, ctxt, master_commits)
-            .await
-            .map_err(|e| format!("error comparing commits: {}", e))?
-            .ok_or_else(|| format!("could not find end commit for bound {:?}", end))?;
+    let start = if let Some(offset) = body.offset {
+        let index = ctxt.index.load();
+        let commit = crate::selector::nth_commit_before(&index, &end, offset).ok_or_else(|| {
+            format!("could not find a commit {offset} entries before bound {end:?}")
+        })?;
+        Bound::Commit(commit.sha)
+    } else {
+        body.start
+    };
+    let comparison = compare_given_commits(start, end.clone(), body.stat, ctxt, master_commits)
+        .await
+        .map_err(|e| format!("error comparing commits: {}", e))?
+        .ok_or_else(|| format!("could not find end commit for bound {:?}", end))?;
 
     let conn = ctxt.conn().await;
     let prev = comparison.prev(master_commits);
@@ -203,6 +211,127 @@ pub async fn handle_compare(
     })
 }
 
+/// Handles a request for plain per-test-case deltas between two artifacts.
+///
+/// Unlike `handle_compare`, this reports absolute and percentage deltas for
+/// the union of test cases measured in either artifact (with a `null` on the
+/// missing side), so that clients don't have to fetch both sides and compute
+/// the diffs themselves.
+pub async fn handle_compare_deltas(
+    body: api::compare::Request,
+    ctxt: &SiteCtxt,
+) -> api::ServerResult<api::compare::Response> {
+    log::info!("handle_compare_deltas({:?})", body);
+
+    let start = body.start;
+    let end = body.end;
+    let a = ctxt
+        .artifact_id_for_bound(start.clone(), true)
+        .ok_or(format!("could not find start commit for bound {:?}", start))?;
+    let b = ctxt
+        .artifact_id_for_bound(end.clone(), false)
+        .ok_or(format!("could not find end commit for bound {:?}", end))?;
+
+    // Refuse cross-triple comparisons up front: the deltas would be
+    // meaningless, and silently returning them is worse than an error.
+    verify_matching_triples(ctxt, &a, &b).await?;
+
+    let aids = Arc::new(vec![a.clone(), b.clone()]);
+
+    let mut responses = ctxt
+        .statistic_series(CompileBenchmarkQuery::all_for_metric(body.stat), aids)
+        .await?;
+    let statistics_for_a = statistics_from_series(&mut responses);
+    let statistics_for_b = statistics_from_series(&mut responses);
+
+    let mut test_cases: HashSet<_> = statistics_for_a.keys().cloned().collect();
+    test_cases.extend(statistics_for_b.keys().cloned());
+
+    // The relative delta (in percent) above which a test case is flagged as
+    // significant; configurable to let deployments tune the noise floor.
+    let significance_threshold = ctxt.config.significant_delta_percent;
+
+    let mut deltas = test_cases
+        .into_iter()
+        .map(|test_case| {
+            let a = statistics_for_a.get(&test_case).copied();
+            let b = statistics_for_b.get(&test_case).copied();
+            let delta = match (a, b) {
+                (Some(a), Some(b)) => Some(b - a),
+                _ => None,
+            };
+            let percent = match (a, b) {
+                (Some(a), Some(b)) if a != 0.0 => Some((b - a) / a * 100.0),
+                _ => None,
+            };
+            api::compare::BenchmarkDelta {
+                benchmark: test_case.benchmark.to_string(),
+                profile: test_case.profile.to_string(),
+                scenario: test_case.scenario.to_string(),
+                backend: test_case.backend.to_string(),
+                a,
+                b,
+                delta,
+                percent,
+                significant: percent
+                    .map(|percent| percent.abs() >= significance_threshold)
+                    .unwrap_or(false),
+            }
+        })
+        .collect::<Vec<_>>();
+    deltas.sort_by(|x, y| {
+        (&x.benchmark, &x.profile, &x.scenario, &x.backend).cmp(&(
+            &y.benchmark,
+            &y.profile,
+            &y.scenario,
+            &y.backend,
+        ))
+    });
+
+    Ok(api::compare::Response {
+        a: a.to_string(),
+        b: b.to_string(),
+        num_significant: deltas.iter().filter(|delta| delta.significant).count(),
+        deltas,
+    })
+}
+
+/// The target triple an artifact was benchmarked for, according to its
+/// recorded environment fingerprint. `None` for artifacts collected before
+/// fingerprints (or before the fingerprint carried the triple).
+async fn artifact_triple(ctxt: &SiteCtxt, artifact: &ArtifactId) -> Option<String> {
+    let artifact_row_id = artifact.lookup(&ctxt.index.load())?;
+    let fingerprint = ctxt.conn().await.get_environment(artifact_row_id).await?;
+    serde_json::from_str::<serde_json::Value>(&fingerprint)
+        .ok()?
+        .get("triple")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+/// Errors out when the two artifacts were benchmarked for different target
+/// triples, since comparing e.g. aarch64 numbers against x86_64 numbers
+/// silently produces a nonsensical diff. Artifacts with no recorded triple
+/// are given the benefit of the doubt.
+async fn verify_matching_triples(
+    ctxt: &SiteCtxt,
+    a: &ArtifactId,
+    b: &ArtifactId,
+) -> Result<(), String> {
+    if let (Some(triple_a), Some(triple_b)) = (
+        artifact_triple(ctxt, a).await,
+        artifact_triple(ctxt, b).await,
+    ) {
+        if triple_a != triple_b {
+            return Err(format!(
+                "cannot compare {a} ({triple_a}) against {b} ({triple_b}): \
+                 the artifacts were benchmarked for different target triples"
+            ));
+        }
+    }
+    Ok(())
+}
+
 async fn populate_report(
     comparison: &ArtifactComparison,
     benchmark_map: &HashMap<Benchmark, Category>,
@@ -730,6 +859,11 @@ async fn compare_given_commits(
         Some(b) => b,
         None => return Ok(None),
     };
+
+    // Refuse cross-triple comparisons here too, so the UI cannot present
+    // e.g. aarch64 numbers diffed against x86_64 numbers as a regression.
+    verify_matching_triples(ctxt, &a, &b).await?;
+
     let aids = Arc::new(vec![a.clone(), b.clone()]);
 
     // get all crates, cache, and profile combinations for the given metric
@@ -777,7 +911,16 @@ async fn compare_given_commits(
         b: ArtifactDescription::for_artifact(&*conn, b.clone(), master_commits).await,
         compile_comparisons,
         runtime_comparisons,
-        newly_failed_benchmarks: errors_in_b.into_iter().collect(),
+        newly_failed_benchmarks: errors_in_b
+            .into_iter()
+            .map(|(name, error)| {
+                // Stored errors may be structured (JSON) or bare strings from
+                // older collectors; render both as human-readable text.
+                let error =
+                    collector::compile::benchmark::BenchmarkError::from_stored(&error).to_string();
+                (name, error)
+            })
+            .collect(),
     }))
 }
 
@@ -831,6 +974,8 @@ fn previous_commits(
                     sha: c.sha.clone(),
                     date: database::Date(c.time),
                     r#type: CommitType::Master,
+                    message: None,
+                    pr: c.pr,
                 });
                 from = new.clone();
                 prevs.push(new);
@@ -1133,6 +1278,12 @@ impl HistoricalData {
     // (q1, q3)
     fn quartiles(&self) -> (f64, f64) {
         let pcs = self.percent_changes();
+        // With fewer than two data points there are no deltas to take
+        // quartiles of. Return zeros so the significance threshold stays
+        // finite instead of indexing out of bounds below.
+        if pcs.is_empty() {
+            return (0.0, 0.0);
+        }
         fn median(data: &[f64]) -> f64 {
             if data.len() % 2 == 0 {
                 (data[(data.len() - 1) / 2] + data[data.len() / 2]) / 2.0
@@ -1713,6 +1864,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn single_datum_significance_threshold_is_finite() {
+        // A single data point produces no deltas; the threshold must come out
+        // as a finite number (zero) rather than panicking or yielding NaN.
+        let data = HistoricalData { data: vec![5.0] };
+        let threshold = data.significance_threshold();
+        assert!(threshold.is_finite());
+        assert_eq!(threshold, 0.0);
+    }
+
     #[test]
     fn parse_metric_instructions() {
         let metric: Metric = serde_json::from_str(r#""instructions:u""#).unwrap();
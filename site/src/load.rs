@@ -98,6 +98,45 @@ pub struct Keys {
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub keys: Keys,
+    /// How many days back from the current time master commits are still
+    /// considered missing (i.e., queued for benchmarking).
+    ///
+    /// Don't set this beyond the CI artifact retention period (168 days at
+    /// the time of writing) -- commits whose artifacts have expired from
+    /// static.rust-lang.org can never be benchmarked, so they would clog the
+    /// queue.
+    #[serde(default = "default_missing_commits_days")]
+    pub missing_commits_days: i64,
+    /// Relative delta (in percent) above which a test case counts as a
+    /// significant change in the /perf/compare endpoint.
+    #[serde(default = "default_significant_delta_percent")]
+    pub significant_delta_percent: f64,
+    /// How many hours the most recent benchmarked commit may lag behind the
+    /// current time before /perf/info reports the data as stale.
+    #[serde(default = "default_staleness_threshold_hours")]
+    pub staleness_threshold_hours: f64,
+    /// Origins allowed to make cross-origin requests to the API. An entry of
+    /// `*` (the default) allows every origin, matching the site's historical
+    /// behavior; a list of specific origins restricts CORS to those, and an
+    /// empty list disables cross-origin access entirely.
+    #[serde(default = "default_allowed_origins")]
+    pub allowed_origins: Vec<String>,
+}
+
+fn default_missing_commits_days() -> i64 {
+    29
+}
+
+fn default_significant_delta_percent() -> f64 {
+    1.0
+}
+
+fn default_staleness_threshold_hours() -> f64 {
+    24.0
+}
+
+fn default_allowed_origins() -> Vec<String> {
+    vec!["*".to_string()]
 }
 
 #[derive(Debug)]
@@ -126,6 +165,9 @@ pub struct SiteCtxt {
     pub config: Config,
     /// Cached site landing page
     pub landing_page: ArcSwap<Option<Arc<crate::api::graphs::Response>>>,
+    /// Cached /perf/info response; computed on first request and invalidated
+    /// when the index is reloaded
+    pub info_page: ArcSwap<Option<Arc<crate::api::info::Response>>>,
     /// Index of various common queries
     pub index: ArcSwap<database::Index>,
     /// Cached master-branch Rust commits
@@ -169,9 +211,20 @@ impl SiteCtxt {
                     github_api_token: std::env::var("GITHUB_API_TOKEN").ok(),
                     github_webhook_secret: std::env::var("GITHUB_WEBHOOK_SECRET").ok(),
                 },
+                missing_commits_days: default_missing_commits_days(),
+                significant_delta_percent: default_significant_delta_percent(),
+                staleness_threshold_hours: default_staleness_threshold_hours(),
+                allowed_origins: default_allowed_origins(),
             }
         };
 
+        if config.keys.github_webhook_secret.is_none() {
+            log::warn!(
+                "no webhook secret configured; /perf/onpush will accept \
+                 unauthenticated requests"
+            );
+        }
+
         let master_commits = MasterCommitCache::download().await?;
 
         Ok(Self {
@@ -180,6 +233,7 @@ impl SiteCtxt {
             master_commits: Arc::new(ArcSwap::new(Arc::new(master_commits))),
             pool,
             landing_page: ArcSwap::new(Arc::new(None)),
+            info_page: ArcSwap::new(Arc::new(None)),
             self_profile_cache: Mutex::new(SelfProfileCache::new(CACHED_SELF_PROFILE_COUNT)),
         })
     }
@@ -207,6 +261,7 @@ impl SiteCtxt {
             queued_pr_commits,
             in_progress_artifacts,
             all_commits,
+            self.config.missing_commits_days,
         )
     }
 
@@ -326,6 +381,7 @@ fn calculate_missing(
     queued_pr_commits: Vec<database::QueuedCommit>,
     in_progress_artifacts: Vec<ArtifactId>,
     all_commits: HashSet<String>,
+    missing_commits_days: i64,
 ) -> Vec<(Commit, MissingReason)> {
     calculate_missing_from(
         master_commits,
@@ -333,10 +389,12 @@ fn calculate_missing(
         in_progress_artifacts,
         all_commits,
         Utc::now(),
+        missing_commits_days,
     )
 }
 
-/// Calculate the missing commits filtering out any that are 29 days or older than the supplied time.
+/// Calculate the missing commits filtering out any that are `missing_commits_days` days or older
+/// than the supplied time.
 ///
 /// This is used by `calculate_missing` is exists as a separate function for testing purposes.
 fn calculate_missing_from(
@@ -345,16 +403,19 @@ fn calculate_missing_from(
     in_progress_artifacts: Vec<ArtifactId>,
     mut all_commits: HashSet<String>,
     time: chrono::DateTime<chrono::Utc>,
+    missing_commits_days: i64,
 ) -> Vec<(Commit, MissingReason)> {
     let mut queue = master_commits
         .into_iter()
-        .filter(|c| time.signed_duration_since(c.time) < Duration::days(29))
+        .filter(|c| time.signed_duration_since(c.time) < Duration::days(missing_commits_days))
         .map(|c| {
             (
                 Commit {
                     sha: c.sha,
                     date: Date(c.time),
                     r#type: CommitType::Master,
+                    message: None,
+                    pr: c.pr,
                 },
                 // All recent master commits should have an associated PR
                 MissingReason::Master {
@@ -396,6 +457,8 @@ fn calculate_missing_from(
                 sha: sha.to_string(),
                 date: commit_date.unwrap_or(Date::empty()),
                 r#type: CommitType::Try,
+                message: None,
+                pr: Some(pr),
             },
             MissingReason::Try {
                 pr,
@@ -609,6 +672,8 @@ mod tests {
                     sha: "b".into(),
                     date: database::Date(time),
                     r#type: CommitType::Master,
+                    message: None,
+                    pr: Some(1),
                 },
                 MissingReason::Master {
                     pr: 1,
@@ -621,6 +686,8 @@ mod tests {
                     sha: "a".into(),
                     date: database::Date(time),
                     r#type: CommitType::Master,
+                    message: None,
+                    pr: Some(2),
                 },
                 MissingReason::Master {
                     pr: 2,
@@ -633,6 +700,8 @@ mod tests {
                     sha: "try-on-a".into(),
                     date: database::Date(time),
                     r#type: CommitType::Try,
+                    message: None,
+                    pr: Some(3),
                 },
                 MissingReason::Try {
                     pr: 3,
@@ -650,6 +719,7 @@ mod tests {
             in_progress_artifacts,
             all_commits,
             time,
+            29,
         );
         assert_eq!(expected, found, "{:#?} != {:#?}", expected, found);
     }
@@ -718,6 +788,8 @@ mod tests {
                     sha: "123".into(),
                     date: database::Date(time),
                     r#type: CommitType::Master,
+                    message: None,
+                    pr: Some(11),
                 },
                 MissingReason::Master {
                     pr: 11,
@@ -730,6 +802,8 @@ mod tests {
                     sha: "foo".into(),
                     date: database::Date(time),
                     r#type: CommitType::Master,
+                    message: None,
+                    pr: Some(77),
                 },
                 MissingReason::Master {
                     pr: 77,
@@ -742,6 +816,8 @@ mod tests {
                     sha: "baz".into(),
                     date: database::Date(time),
                     r#type: CommitType::Try,
+                    message: None,
+                    pr: Some(101),
                 },
                 MissingReason::Try {
                     pr: 101,
@@ -760,7 +836,8 @@ mod tests {
                 queued_pr_commits,
                 in_progress_artifacts,
                 all_commits,
-                time
+                time,
+                29
             )
         );
     }
@@ -16,27 +16,181 @@ pub use graph::{
 pub use next_artifact::handle_next_artifact;
 pub use self_profile::{
     handle_self_profile, handle_self_profile_processed_download, handle_self_profile_raw,
-    handle_self_profile_raw_download,
+    handle_self_profile_raw_download, handle_self_profile_raw_redirect,
 };
 pub use status_page::handle_status_page;
 
-use crate::api::{info, ServerResult};
+use std::sync::Arc;
+
+use crate::api::{errors, index_dump, info, ServerResult};
 use crate::load::SiteCtxt;
+use database::Lookup;
+
+pub async fn handle_info(ctxt: Arc<SiteCtxt>) -> info::Response {
+    // Everything but `is_stale` only changes when new artifacts land (which
+    // also reloads the index), so compute it once and serve the cached copy
+    // until the index is reloaded. `is_stale` compares against the current
+    // time and is refreshed on every request.
+    let is_stale = |as_of: &Option<database::Date>| match as_of {
+        Some(date) => {
+            let hours_since_last = (chrono::Utc::now() - date.0).num_minutes() as f64 / 60.0;
+            hours_since_last > ctxt.config.staleness_threshold_hours
+        }
+        // No data at all is the most stale a deployment can be.
+        None => true,
+    };
+
+    if let Some(cached) = &**ctxt.info_page.load() {
+        let mut response = (**cached).clone();
+        response.is_stale = is_stale(&response.as_of);
+        return response;
+    }
 
-pub fn handle_info(ctxt: &SiteCtxt) -> info::Response {
     let mut compile_metrics = ctxt.index.load().compile_metrics();
     compile_metrics.sort();
 
     let mut runtime_metrics = ctxt.index.load().runtime_metrics();
     runtime_metrics.sort();
 
-    info::Response {
+    let stat_units = compile_metrics
+        .iter()
+        .chain(runtime_metrics.iter())
+        .filter_map(|metric| {
+            let unit = metric.parse::<database::metric::Metric>().ok()?.unit();
+            Some((metric.clone(), unit.as_str().to_string()))
+        })
+        .collect();
+
+    // Triples come from the per-artifact environment fingerprints, so a
+    // deployment collecting on several architectures reports all of them. A
+    // database from before fingerprints carried the triple reports none.
+    let mut triples: Vec<String> = ctxt
+        .conn()
+        .await
+        .get_environments()
+        .await
+        .iter()
+        .filter_map(|fingerprint| {
+            Some(
+                serde_json::from_str::<serde_json::Value>(fingerprint)
+                    .ok()?
+                    .get("triple")?
+                    .as_str()?
+                    .to_string(),
+            )
+        })
+        .collect();
+    triples.sort();
+    triples.dedup();
+
+    let as_of = ctxt.index.load().commits().last().map(|d| d.date);
+    let response = info::Response {
         compile_metrics,
         runtime_metrics,
-        as_of: ctxt.index.load().commits().last().map(|d| d.date),
-    }
+        stat_units,
+        is_stale: is_stale(&as_of),
+        as_of,
+        triples,
+    };
+    ctxt.info_page
+        .store(std::sync::Arc::new(Some(std::sync::Arc::new(
+            response.clone(),
+        ))));
+    response
 }
 
 pub async fn handle_collected() -> ServerResult<()> {
     Ok(())
 }
+
+/// Lists the benchmarks that errored during the collection of a commit, so
+/// that a gap in the data can be traced back to its cause instead of just
+/// showing up as an absent point.
+pub async fn handle_errors(
+    request: errors::Request,
+    ctxt: Arc<SiteCtxt>,
+) -> ServerResult<errors::Response> {
+    let idx = ctxt.index.load();
+    let artifact = crate::selector::artifact_id_for_bound(&idx, request.commit.clone(), true)
+        .ok_or_else(|| {
+            format!(
+                "bound {:?} does not resolve to a benchmarked commit",
+                request.commit
+            )
+        })?;
+    let artifact_row_id = artifact
+        .lookup(&idx)
+        .ok_or_else(|| format!("artifact {artifact} not found in index"))?;
+
+    let conn = ctxt.conn().await;
+    let errors = conn
+        .get_error(artifact_row_id)
+        .await
+        .into_iter()
+        .map(|(name, error)| {
+            let error = collector::compile::benchmark::BenchmarkError::from_stored(&error);
+            (name, error.to_string())
+        })
+        .collect();
+
+    let artifact = match artifact {
+        database::ArtifactId::Commit(commit) => commit.sha,
+        database::ArtifactId::Tag(tag) => tag,
+    };
+    Ok(errors::Response { artifact, errors })
+}
+
+/// Dumps the index (commits, artifacts, and the test cases with data) in one
+/// response, so offline analysis can discover the dataset's structure without
+/// scraping per-commit endpoints. Read-only; the response can be large, so it
+/// goes through the compressing handler.
+pub async fn handle_index_dump(ctxt: Arc<SiteCtxt>) -> ServerResult<index_dump::Response> {
+    let idx = ctxt.index.load();
+
+    let mut artifacts: Vec<String> = idx.artifacts().map(|a| a.to_string()).collect();
+    artifacts.sort();
+
+    // The index stores one entry per (test case, metric); fold the metrics
+    // into their test case so clients do not have to.
+    let mut compile_test_cases: std::collections::BTreeMap<_, Vec<String>> = Default::default();
+    for ((benchmark, profile, scenario, backend, metric), _) in idx.compile_statistic_descriptions()
+    {
+        compile_test_cases
+            .entry((
+                benchmark.to_string(),
+                profile.to_string(),
+                scenario.to_string(),
+                backend.to_string(),
+            ))
+            .or_default()
+            .push(metric.to_string());
+    }
+    let compile_test_cases = compile_test_cases
+        .into_iter()
+        .map(|((benchmark, profile, scenario, backend), mut metrics)| {
+            metrics.sort();
+            index_dump::CompileTestCase {
+                benchmark,
+                profile,
+                scenario,
+                backend,
+                metrics,
+            }
+        })
+        .collect();
+
+    let mut runtime_benchmarks: Vec<String> = idx
+        .runtime_statistic_descriptions()
+        .map(|((benchmark, _), _)| benchmark.to_string())
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+    runtime_benchmarks.sort();
+
+    Ok(index_dump::Response {
+        commits: idx.commits(),
+        artifacts,
+        compile_test_cases,
+        runtime_benchmarks,
+    })
+}
@@ -27,7 +27,7 @@ pub async fn handle_compile_detail_graphs(
         &ctxt,
         request.start,
         request.end,
-    ));
+    )?);
 
     let scenario = request.scenario.parse()?;
     let interpolated_responses: Vec<_> = ctxt
@@ -99,6 +99,10 @@ pub async fn handle_compile_detail_sections(
             })
     }
 
+    // Self-profiles have to be fetched per commit, so cap the series length
+    // regardless of what the client asks for.
+    const MAX_SECTION_POINTS: usize = 20;
+
     // Doc queries are not split into the classic frontend/backend/linker parts.
     let (before, after) = if request.profile != "doc" {
         tokio::join!(
@@ -121,7 +125,46 @@ pub async fn handle_compile_detail_sections(
         (None, None)
     };
 
-    Ok(detail_sections::Response { before, after })
+    // Optionally sample commits across the range as well, so that the client
+    // can plot each section over time and see which one regressed.
+    let series = match request.points {
+        Some(points) if request.profile != "doc" => {
+            let artifact_ids =
+                master_artifact_ids_for_range(&ctxt, request.start.clone(), request.end.clone())?;
+            let artifact_ids =
+                downsample_artifact_ids(artifact_ids, points.min(MAX_SECTION_POINTS));
+            let mut series = Vec::with_capacity(artifact_ids.len());
+            // Sequential on purpose: each point may download a self-profile
+            // archive, and hammering the archive server with 20 concurrent
+            // downloads helps nobody.
+            for aid in artifact_ids {
+                let (commit, date) = match &aid {
+                    ArtifactId::Commit(c) => (c.sha.clone(), c.date.0.timestamp()),
+                    ArtifactId::Tag(_) => unreachable!(),
+                };
+                // Commits without self-profile data are skipped rather than
+                // failing the whole series.
+                if let Some(sections) =
+                    calculate_sections(&ctxt, aid, &request.benchmark, &request.profile, scenario)
+                        .await
+                {
+                    series.push(detail_sections::CommitSections {
+                        commit,
+                        date,
+                        sections,
+                    });
+                }
+            }
+            Some(series)
+        }
+        _ => None,
+    };
+
+    Ok(detail_sections::Response {
+        before,
+        after,
+        series,
+    })
 }
 
 pub async fn handle_runtime_detail_graphs(
@@ -134,7 +177,7 @@ pub async fn handle_runtime_detail_graphs(
         &ctxt,
         request.start,
         request.end,
-    ));
+    )?);
 
     let interpolated_responses: Vec<_> = ctxt
         .statistic_series(
@@ -180,6 +223,7 @@ pub async fn handle_graphs(
             benchmark: None,
             scenario: None,
             profile: None,
+            max_points: None,
         };
 
     if is_default_query {
@@ -201,11 +245,11 @@ async fn create_graphs(
     request: graphs::Request,
     ctxt: &SiteCtxt,
 ) -> ServerResult<graphs::Response> {
-    let artifact_ids = Arc::new(master_artifact_ids_for_range(
-        ctxt,
-        request.start,
-        request.end,
-    ));
+    let mut artifact_ids = master_artifact_ids_for_range(ctxt, request.start, request.end)?;
+    if let Some(max_points) = request.max_points {
+        artifact_ids = downsample_artifact_ids(artifact_ids, max_points);
+    }
+    let artifact_ids = Arc::new(artifact_ids);
     let mut benchmarks = HashMap::new();
 
     let create_selector = |filter: &Option<String>| -> Selector<String> {
@@ -276,13 +320,47 @@ fn artifact_ids_to_commits(artifact_ids: Arc<Vec<ArtifactId>>) -> Vec<(i64, Stri
         .collect()
 }
 
-/// Returns master commit artifact IDs for the given range.
-fn master_artifact_ids_for_range(ctxt: &SiteCtxt, start: Bound, end: Bound) -> Vec<ArtifactId> {
-    ctxt.data_range(start..=end)
+/// Returns master commit artifact IDs for the given range, rejecting commit
+/// bounds that do not refer to a known commit instead of silently returning
+/// an empty range.
+fn master_artifact_ids_for_range(
+    ctxt: &SiteCtxt,
+    start: Bound,
+    end: Bound,
+) -> ServerResult<Vec<ArtifactId>> {
+    let index = ctxt.index.load();
+    crate::selector::verify_bound(&index, &start)?;
+    crate::selector::verify_bound(&index, &end)?;
+
+    Ok(ctxt
+        .data_range(start..=end)
         .into_iter()
         .filter(|commit| commit.is_master())
         .map(|commit| commit.into())
-        .collect()
+        .collect())
+}
+
+/// Reduces the artifact list to at most `max_points` entries by bucketing
+/// adjacent commits and keeping one representative per bucket. The first and
+/// last commits are always preserved, so the endpoints of the graph stay
+/// real data points.
+fn downsample_artifact_ids(artifact_ids: Vec<ArtifactId>, max_points: usize) -> Vec<ArtifactId> {
+    let max_points = max_points.max(2);
+    let len = artifact_ids.len();
+    if len <= max_points {
+        return artifact_ids;
+    }
+
+    let mut selected = Vec::with_capacity(max_points);
+    let mut last_idx = None;
+    for point in 0..max_points {
+        let idx = point * (len - 1) / (max_points - 1);
+        if last_idx != Some(idx) {
+            selected.push(artifact_ids[idx].clone());
+            last_idx = Some(idx);
+        }
+    }
+    selected
 }
 
 #[allow(clippy::type_complexity)]
@@ -356,6 +434,7 @@ fn graph_series(
 ) -> graphs::Series {
     let mut graph_series = graphs::Series {
         points: Vec::new(),
+        delta_percent: 0.0,
         interpolated_indices: Default::default(),
     };
 
@@ -382,6 +461,9 @@ fn graph_series(
         if is_interpolated.as_bool() {
             graph_series.interpolated_indices.insert(idx as u16);
         }
+
+        // The last iteration leaves the net first-to-last change in place.
+        graph_series.delta_percent = percent_first as f32;
     }
 
     graph_series
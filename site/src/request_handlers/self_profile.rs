@@ -414,6 +414,36 @@ async fn tarball(resp: reqwest::Response, mut sender: hyper::body::Sender) {
     }
 }
 
+/// Redirects to the stored raw self-profile artifact for the requested
+/// commit/benchmark/profile/scenario, so it can be fetched directly for local
+/// analysis with `summarize`/`crox`. Responds with 404 when no self-profile
+/// was recorded for that combination.
+pub async fn handle_self_profile_raw_redirect(
+    body: self_profile_raw::Request,
+    ctxt: &SiteCtxt,
+) -> http::Response<hyper::Body> {
+    log::info!("handle_self_profile_raw_redirect({:?})", body);
+    match handle_self_profile_raw(body, ctxt).await {
+        Ok(v) => http::Response::builder()
+            .status(StatusCode::FOUND)
+            .header(hyper::header::LOCATION, v.url)
+            .body(hyper::Body::empty())
+            .unwrap(),
+        Err(e) => {
+            // A missing artifact surfaces either as no database results or as
+            // an unresolvable download URL; everything else is a bad request.
+            let status = if e.starts_with("No results") || e.contains("did not resolve") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+            let mut resp = http::Response::new(e.into());
+            *resp.status_mut() = status;
+            resp
+        }
+    }
+}
+
 pub async fn handle_self_profile_raw(
     body: self_profile_raw::Request,
     ctxt: &SiteCtxt,
@@ -436,6 +466,8 @@ pub async fn handle_self_profile_raw(
                 sha: body.commit.clone(),
                 date: database::Date::empty(),
                 r#type: CommitType::Master,
+                message: None,
+                pr: None,
             }),
             bench_name,
             profile,
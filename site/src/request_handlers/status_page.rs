@@ -58,6 +58,8 @@ pub async fn handle_status_page(ctxt: Arc<SiteCtxt>) -> status::Response {
             errors: errors
                 .into_iter()
                 .map(|(name, error)| {
+                    let error = collector::compile::benchmark::BenchmarkError::from_stored(&error)
+                        .to_string();
                     let error = prettify_log(&error).unwrap_or(error);
                     status::BenchmarkError { name, error }
                 })
@@ -16,11 +16,14 @@ use std::time::Instant;
 /// If not found in those commits, searches through the artifacts in the index.
 pub fn artifact_id_for_bound(data: &Index, bound: Bound, is_left: bool) -> Option<ArtifactId> {
     let commits = data.commits();
+    let bound = bound.resolve(&commits);
     let commit = if is_left {
         commits
             .iter()
             .find(|commit| bound.left_match(commit))
             .cloned()
+    } else if let Bound::Date(date) = &bound {
+        commit_at_or_before(&commits, *date).cloned()
     } else {
         commits
             .iter()
@@ -32,14 +35,52 @@ pub fn artifact_id_for_bound(data: &Index, bound: Bound, is_left: bool) -> Optio
             .find(|aid| match &bound {
                 Bound::Commit(c) => *c == **aid,
                 Bound::Date(_) => false,
+                Bound::Relative(_) => false,
                 Bound::None => false,
             })
             .map(|aid| ArtifactId::Tag(aid.to_string()))
     })
 }
 
+/// Checks that a `Bound::Commit` refers to a commit or artifact that is
+/// actually present in the index. A mistyped SHA would otherwise silently
+/// produce an empty range. Date and empty bounds keep their nearest-match
+/// semantics and always pass.
+pub fn verify_bound(data: &Index, bound: &Bound) -> Result<(), String> {
+    if let Bound::Commit(sha) = bound {
+        let known = data.commits().iter().any(|commit| commit.sha == **sha)
+            || data.artifacts().any(|aid| *aid == *sha);
+        if !known {
+            return Err(format!("unknown commit or artifact tag `{sha}`"));
+        }
+    }
+    Ok(())
+}
+
+/// Resolves `reference` to a commit in the index and steps back `n` entries
+/// in the commit ordering. This gives "N builds ago" semantics, which is
+/// often more useful than a calendar offset because commit cadence varies.
+pub fn nth_commit_before(data: &Index, reference: &Bound, n: usize) -> Option<Commit> {
+    let commits = data.commits();
+    let idx = commits
+        .iter()
+        .rposition(|commit| reference.right_match(commit))?;
+    idx.checked_sub(n).map(|idx| commits[idx].clone())
+}
+
+/// Finds the newest master commit dated at or before `date`. The commit list
+/// from the index is sorted by date, so the cutoff is located with a binary
+/// search instead of the reverse linear scan a `right_match` lookup does;
+/// only the (rare) trailing try commits after the cutoff are skipped.
+pub fn commit_at_or_before(commits: &[Commit], date: chrono::NaiveDate) -> Option<&Commit> {
+    let end = commits.partition_point(|commit| commit.date.0.date_naive() <= date);
+    commits[..end].iter().rfind(|commit| commit.is_master())
+}
+
 pub fn range_subset(data: Vec<Commit>, range: RangeInclusive<Bound>) -> Vec<Commit> {
     let (a, b) = range.into_inner();
+    let a = a.resolve(&data);
+    let b = b.resolve(&data);
 
     let left_idx = data.iter().position(|commit| a.left_match(commit));
     let right_idx = data.iter().rposition(|commit| b.right_match(commit));
@@ -100,3 +141,52 @@ impl StatisticSeriesExt for StatisticSeries {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::commit_at_or_before;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use database::{Commit, CommitType, Date};
+
+    fn commit(sha: &str, year: i32, month: u32, day: u32) -> Commit {
+        Commit {
+            sha: sha.to_string(),
+            date: Date(Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()),
+            r#type: CommitType::Master,
+            message: None,
+            pr: None,
+        }
+    }
+
+    #[test]
+    fn commit_at_or_before_snaps_to_dates() {
+        let commits = vec![
+            commit("a", 2024, 1, 1),
+            commit("b", 2024, 1, 3),
+            commit("c", 2024, 1, 7),
+        ];
+        let at = |y, m, d| {
+            commit_at_or_before(&commits, NaiveDate::from_ymd_opt(y, m, d).unwrap())
+                .map(|c| c.sha.as_str())
+        };
+
+        // Exact hit.
+        assert_eq!(at(2024, 1, 3), Some("b"));
+        // Between commits, snap backwards.
+        assert_eq!(at(2024, 1, 5), Some("b"));
+        // Before the first commit there is nothing to snap to.
+        assert_eq!(at(2023, 12, 31), None);
+        // After the last commit, the newest one wins.
+        assert_eq!(at(2024, 2, 1), Some("c"));
+    }
+
+    #[test]
+    fn commit_at_or_before_skips_try_commits() {
+        let mut try_commit = commit("try", 2024, 1, 4);
+        try_commit.r#type = CommitType::Try;
+        let commits = vec![commit("a", 2024, 1, 1), try_commit];
+
+        let found = commit_at_or_before(&commits, NaiveDate::from_ymd_opt(2024, 1, 6).unwrap());
+        assert_eq!(found.map(|c| c.sha.as_str()), Some("a"));
+    }
+}
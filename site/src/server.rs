@@ -98,23 +98,6 @@ impl UpdatingStatus {
 }
 
 impl Server {
-    /// Handle a synchrnous HTTP GET request
-    fn handle_get<F, S>(&self, req: &Request, handler: F) -> Result<Response, ServerError>
-    where
-        F: FnOnce(&SiteCtxt) -> S,
-        S: Serialize,
-    {
-        check_http_method!(*req.method(), http::Method::GET);
-        let ctxt = self.ctxt.clone();
-        let ctxt = ctxt.read();
-        let ctxt = ctxt.as_ref().unwrap();
-        let result = handler(ctxt);
-        Ok(http::Response::builder()
-            .header_typed(ContentType::json())
-            .body(hyper::Body::from(serde_json::to_string(&result).unwrap()))
-            .unwrap())
-    }
-
     /// Handle an asynchrnous HTTP GET request
     async fn handle_get_async<F, R, S>(
         &self,
@@ -132,7 +115,7 @@ impl Server {
         let result = handler(ctxt).await;
         Ok(http::Response::builder()
             .header_typed(ContentType::json())
-            .body(hyper::Body::from(serde_json::to_string(&result).unwrap()))
+            .body(hyper::Body::from(serialize_response(&result, req)))
             .unwrap())
     }
 
@@ -157,7 +140,7 @@ impl Server {
                 let response = http::Response::builder()
                     .header_typed(ContentType::json())
                     .header_typed(CacheControl::new().with_no_cache().with_no_store());
-                let body = serde_json::to_vec(&result).unwrap();
+                let body = serialize_response(&result, req);
                 maybe_compressed_response(response, body, compression)
             }
             Err(err) => http::Response::builder()
@@ -187,6 +170,43 @@ impl Server {
         false
     }
 
+    /// Authorizes a request against the configured webhook secret, accepted
+    /// either as a `Bearer` token or as a `?token=` query parameter. When no
+    /// secret is configured the endpoint stays open for local development;
+    /// `SiteCtxt` logs a warning about that at startup.
+    fn check_push_auth(&self, req: &Request) -> bool {
+        let secret = {
+            let ctxt = self.ctxt.read();
+            let ctxt = ctxt.as_ref().unwrap();
+            ctxt.config.keys.github_webhook_secret.clone()
+        };
+        let Some(secret) = secret else {
+            return true;
+        };
+
+        if let Some(auth) = req
+            .headers()
+            .get(Authorization::<headers::authorization::Bearer>::name())
+        {
+            if let Ok(auth) =
+                Authorization::<headers::authorization::Bearer>::decode(&mut Some(auth).into_iter())
+            {
+                if auth.0.token() == secret {
+                    return true;
+                }
+            }
+        }
+
+        req.uri()
+            .query()
+            .map(|query| {
+                query
+                    .split('&')
+                    .any(|pair| pair.strip_prefix("token=") == Some(secret.as_str()))
+            })
+            .unwrap_or(false)
+    }
+
     async fn handle_metrics(&self, _req: Request) -> Response {
         use prometheus::Encoder;
         let ctxt: Arc<SiteCtxt> = self.ctxt.read().as_ref().unwrap().clone();
@@ -256,9 +276,18 @@ impl Server {
         Response::new(buffer.into())
     }
 
-    async fn handle_push(&self, _req: Request) -> Response {
+    async fn handle_push(&self, req: Request) -> Response {
         static LAST_UPDATE: LazyLock<Mutex<Option<Instant>>> = LazyLock::new(|| Mutex::new(None));
 
+        // Triggering a reload is expensive, so don't let just anyone do it.
+        if !self.check_push_auth(&req) {
+            return http::Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .header_typed(ContentType::text_utf8())
+                .body(hyper::Body::from("missing or invalid token"))
+                .unwrap();
+        }
+
         let last = *LAST_UPDATE.lock();
         if let Some(last) = last {
             let min = 60; // 1 minutes
@@ -300,6 +329,7 @@ impl Server {
 
         // Refresh the landing page
         ctxt.landing_page.store(Arc::new(None));
+        ctxt.info_page.store(Arc::new(None));
 
         // Spawn off a task to post the results of any commit results that we
         // are now aware of.
@@ -371,7 +401,16 @@ async fn serve_req(server: Server, req: Request) -> Result<Response, ServerError
     }
 
     match path {
-        "/perf/info" => return server.handle_get(&req, request_handlers::handle_info),
+        "/perf/info" => {
+            return server
+                .handle_get_async(&req, request_handlers::handle_info)
+                .await;
+        }
+        "/perf/index" => {
+            return server
+                .handle_fallible_get_async(&req, &compression, request_handlers::handle_index_dump)
+                .await;
+        }
         "/perf/dashboard" => {
             return server
                 .handle_get_async(&req, request_handlers::handle_dashboard)
@@ -394,6 +433,14 @@ async fn serve_req(server: Server, req: Request) -> Result<Response, ServerError
                 crate::comparison::handle_triage(input, &ctxt).await,
             ));
         }
+        "/perf/compare" => {
+            let query = check!(parse_query_string(req.uri()));
+            return server
+                .handle_fallible_get_async(&req, &compression, |c| async move {
+                    crate::comparison::handle_compare_deltas(query, &c).await
+                })
+                .await;
+        }
         "/perf/compare-compile-detail-graphs" => {
             let query = check!(parse_query_string(req.uri()));
             return server
@@ -418,6 +465,14 @@ async fn serve_req(server: Server, req: Request) -> Result<Response, ServerError
                 })
                 .await;
         }
+        "/perf/errors" => {
+            let query = check!(parse_query_string(req.uri()));
+            return server
+                .handle_fallible_get_async(&req, &compression, |c| {
+                    request_handlers::handle_errors(query, c)
+                })
+                .await;
+        }
         "/perf/graphs" => {
             let query = check!(parse_query_string(req.uri()));
             return server
@@ -432,6 +487,11 @@ async fn serve_req(server: Server, req: Request) -> Result<Response, ServerError
         "/perf/onpush" => {
             return Ok(server.handle_push(req).await);
         }
+        "/perf/raw_self_profile" => {
+            let ctxt: Arc<SiteCtxt> = server.ctxt.read().as_ref().unwrap().clone();
+            let req = check!(parse_query_string(req.uri()));
+            return Ok(request_handlers::handle_self_profile_raw_redirect(req, &ctxt).await);
+        }
         "/perf/download-raw-self-profile" => {
             let ctxt: Arc<SiteCtxt> = server.ctxt.read().as_ref().unwrap().clone();
             let req = check!(parse_query_string(req.uri()));
@@ -575,6 +635,35 @@ where
     }
 }
 
+/// Whether the client asked for pretty-printed JSON, either with a
+/// `?pretty=1` query parameter or an `Accept: application/json+pretty`
+/// header. Compact output stays the default; this is meant for poking at the
+/// API by hand, not for the frontend.
+fn wants_pretty_json(req: &Request) -> bool {
+    let by_query = req
+        .uri()
+        .query()
+        .map(|query| query.split('&').any(|pair| pair == "pretty=1"))
+        .unwrap_or(false);
+    let by_header = req
+        .headers()
+        .get(hyper::header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .map(|accept| accept.contains("application/json+pretty"))
+        .unwrap_or(false);
+    by_query || by_header
+}
+
+/// Serializes an API response, honoring a pretty-printing request (see
+/// [`wants_pretty_json`]).
+fn serialize_response<S: Serialize>(result: &S, req: &Request) -> Vec<u8> {
+    if wants_pretty_json(req) {
+        serde_json::to_vec_pretty(result).unwrap()
+    } else {
+        serde_json::to_vec(result).unwrap()
+    }
+}
+
 fn parse_query_string<D>(uri: &http::Uri) -> Result<D, Response>
 where
     D: DeserializeOwned,
@@ -771,6 +860,22 @@ fn to_triage_response(result: ServerResult<api::triage::Response>) -> Response {
     }
 }
 
+/// Resolves the `Access-Control-Allow-Origin` value for a request from the
+/// configured origin allowlist. A `*` entry allows every origin; otherwise the
+/// request's `Origin` is echoed back iff it is listed. `None` means no CORS
+/// headers are attached at all.
+fn cors_allow_origin(
+    allowed_origins: &[String],
+    origin: Option<&str>,
+) -> Option<hyper::header::HeaderValue> {
+    if allowed_origins.iter().any(|allowed| allowed == "*") {
+        return Some(hyper::header::HeaderValue::from_static("*"));
+    }
+    origin
+        .filter(|origin| allowed_origins.iter().any(|allowed| allowed == origin))
+        .and_then(|origin| hyper::header::HeaderValue::from_str(origin).ok())
+}
+
 async fn run_server(ctxt: Arc<RwLock<Option<Arc<SiteCtxt>>>>, addr: SocketAddr) {
     let server = Server::new(ctxt);
     let svc = hyper::service::make_service_fn(move |_conn| {
@@ -778,18 +883,58 @@ async fn run_server(ctxt: Arc<RwLock<Option<Arc<SiteCtxt>>>>, addr: SocketAddr)
         async move {
             Ok::<_, hyper::Error>(hyper::service::service_fn(move |req| {
                 let start = std::time::Instant::now();
-                let desc = format!("{} {}", req.method(), req.uri());
+                // Log only the path, not the full URI: query strings can
+                // carry secrets (e.g. the `?token=` webhook auth).
+                let desc = format!("{} {}", req.method(), req.uri().path());
+                let allow_origin = {
+                    let origin = req
+                        .headers()
+                        .get(hyper::header::ORIGIN)
+                        .and_then(|value| value.to_str().ok());
+                    match ctx.ctxt.read().as_ref() {
+                        Some(ctxt) => cors_allow_origin(&ctxt.config.allowed_origins, origin),
+                        // Before the context is loaded, fall back to the
+                        // pre-configuration behavior of allowing everyone.
+                        None => Some(hyper::header::HeaderValue::from_static("*")),
+                    }
+                };
                 serve_req(ctx.clone(), req)
                     .inspect(move |r| {
-                        let dur = start.elapsed();
-                        info!("{}: {:?} {:?}", desc, r.as_ref().map(|r| r.status()), dur)
+                        // One access-log line per request with status and
+                        // latency; turn the `site` log level down to silence
+                        // it in production.
+                        if log::log_enabled!(log::Level::Info) {
+                            let elapsed_ms = start.elapsed().as_millis();
+                            match r {
+                                Ok(r) => {
+                                    info!("{} {} {}ms", desc, r.status().as_u16(), elapsed_ms)
+                                }
+                                Err(e) => info!("{} error={:?} {}ms", desc, e, elapsed_ms),
+                            }
+                        }
                     })
-                    .map(|mut r| {
-                        if let Ok(r) = &mut r {
-                            r.headers_mut().insert(
-                                hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN,
-                                hyper::header::HeaderValue::from_static("*"),
+                    .map(move |mut r| {
+                        if let (Ok(r), Some(allow_origin)) = (&mut r, allow_origin) {
+                            let echoes_origin = allow_origin != "*";
+                            let headers = r.headers_mut();
+                            headers
+                                .insert(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+                            headers.insert(
+                                hyper::header::ACCESS_CONTROL_ALLOW_METHODS,
+                                hyper::header::HeaderValue::from_static("GET, POST, OPTIONS"),
+                            );
+                            headers.insert(
+                                hyper::header::ACCESS_CONTROL_ALLOW_HEADERS,
+                                hyper::header::HeaderValue::from_static("Content-Type"),
                             );
+                            if echoes_origin {
+                                // Responses differ per origin, so caches must
+                                // not reuse them across origins.
+                                headers.insert(
+                                    hyper::header::VARY,
+                                    hyper::header::HeaderValue::from_static("Origin"),
+                                );
+                            }
                         }
                         r
                     })